@@ -0,0 +1,59 @@
+use crate::node_data::PropertyValue;
+use crate::node_id::NodeId;
+use crate::relation::RelationId;
+
+/// A single change applied as part of a [`Batch`].
+#[derive(Clone, Debug)]
+pub enum BatchOp {
+    /// Adds an edge along with its properties and an optional unix expiry time, in seconds.
+    AddEdge {
+        source: NodeId,
+        target: NodeId,
+        relation: RelationId,
+        properties: Vec<(String, PropertyValue)>,
+        expires_at: Option<u64>,
+    },
+    RemoveEdge {
+        source: NodeId,
+        target: NodeId,
+        relation: RelationId,
+    },
+    /// Removes every edge of a node in every relation, along with its labels and properties.
+    RemoveNode { node: NodeId },
+}
+
+/// A condition on the graph that must hold before a [`Batch`] is applied.
+#[derive(Clone, Debug)]
+pub enum Precondition {
+    EdgeAbsent {
+        source: NodeId,
+        target: NodeId,
+        relation: RelationId,
+    },
+    EdgePresent {
+        source: NodeId,
+        target: NodeId,
+        relation: RelationId,
+    },
+    /// The node has fewer than `limit` outgoing edges of the relation, e.g. to enforce a
+    /// follow limit.
+    OutDegreeBelow {
+        node: NodeId,
+        relation: RelationId,
+        limit: u64,
+    },
+}
+
+/// Operations applied all at once, or not at all when any precondition does not hold.
+#[derive(Clone, Debug, Default)]
+pub struct Batch {
+    pub preconditions: Vec<Precondition>,
+    pub ops: Vec<BatchOp>,
+}
+
+/// Returned when a precondition of a [`Batch`] does not hold. None of its operations are applied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FailedPrecondition {
+    /// Position of the failed precondition in [`Batch::preconditions`].
+    pub index: usize,
+}
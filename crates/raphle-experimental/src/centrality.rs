@@ -0,0 +1,180 @@
+use crate::rank::Ranking;
+use crate::rng::Rng;
+use crate::snapshot::Snapshot;
+
+/// Marks nodes not reached yet by a search.
+const UNREACHED: u32 = u32::MAX;
+
+/// Centrality scores computed from shortest paths out of a sample of source nodes.
+pub struct Centrality {
+    pub ranking: Ranking,
+    /// Number of sources searched from, every node of the snapshot when the scores are exact.
+    pub samples: usize,
+}
+
+/// Picks up to `samples` source nodes at random for a given seed, or every node if `samples` is
+/// `None` or reaches the size of the snapshot.
+fn sources(n: usize, samples: Option<usize>, seed: u64) -> Vec<usize> {
+    let mut sources: Vec<usize> = (0..n).collect();
+    if let Some(samples) = samples.filter(|&samples| samples < n) {
+        Rng::new(seed).shuffle(&mut sources);
+        sources.truncate(samples);
+        sources.sort_unstable();
+    }
+    sources
+}
+
+/// A breadth-first search along outgoing edges, its buffers reused from one source to the next.
+struct Search {
+    distances: Vec<u32>,
+    /// Number of shortest paths from the source to each node.
+    paths: Vec<f64>,
+    /// Nodes reached, by increasing distance.
+    order: Vec<u32>,
+}
+
+impl Search {
+    fn new(n: usize) -> Self {
+        Search {
+            distances: vec![UNREACHED; n],
+            paths: vec![0.0; n],
+            order: Vec::new(),
+        }
+    }
+
+    fn run(&mut self, snapshot: &Snapshot, source: usize) {
+        for &node in &self.order {
+            self.distances[node as usize] = UNREACHED;
+            self.paths[node as usize] = 0.0;
+        }
+        self.order.clear();
+
+        self.distances[source] = 0;
+        self.paths[source] = 1.0;
+        self.order.push(source as u32);
+        let mut next = 0;
+        while let Some(&node) = self.order.get(next) {
+            next += 1;
+            let node = node as usize;
+            let distance = self.distances[node] + 1;
+            for &neighbor in snapshot.outgoing(node) {
+                let neighbor = neighbor as usize;
+                if self.distances[neighbor] == UNREACHED {
+                    self.distances[neighbor] = distance;
+                    self.order.push(neighbor as u32);
+                }
+                if self.distances[neighbor] == distance {
+                    self.paths[neighbor] += self.paths[node];
+                }
+            }
+        }
+    }
+}
+
+/// Computes the betweenness of every node, the share of shortest paths between other nodes that
+/// pass through it, with Brandes' algorithm. Given `samples`, only shortest paths from that many
+/// sources picked with `seed` are followed, and the scores are scaled up to estimate the exact
+/// ones. Scores are normalized by the number of pairs of other nodes, as in networkx.
+pub fn betweenness(snapshot: &Snapshot, samples: Option<usize>, seed: u64) -> Centrality {
+    let n = snapshot.len();
+    let sources = sources(n, samples, seed);
+    let mut search = Search::new(n);
+    let mut scores = vec![0.0; n];
+    let mut dependencies = vec![0.0; n];
+
+    for &source in &sources {
+        search.run(snapshot, source);
+        // the dependencies of a node are complete once all nodes further away are done
+        for &node in search.order.iter().rev() {
+            let node = node as usize;
+            let distance = search.distances[node];
+            if distance == 0 {
+                continue;
+            }
+            let share = (1.0 + dependencies[node]) / search.paths[node];
+            for &predecessor in snapshot.incoming(node) {
+                let predecessor = predecessor as usize;
+                if search.distances[predecessor] == distance - 1 {
+                    dependencies[predecessor] += search.paths[predecessor] * share;
+                }
+            }
+            scores[node] += dependencies[node];
+        }
+        for &node in &search.order {
+            dependencies[node as usize] = 0.0;
+        }
+    }
+
+    if n > 2 {
+        let scale = n as f64 / (sources.len() as f64 * ((n - 1) * (n - 2)) as f64);
+        for score in &mut scores {
+            *score *= scale;
+        }
+    }
+    Centrality {
+        ranking: Ranking::new(snapshot, scores),
+        samples: sources.len(),
+    }
+}
+
+/// Computes the closeness of every node from the distances to it from the nodes that can reach
+/// it, scaled down by the share of nodes that can, as networkx does with `wf_improved`. Given
+/// `samples`, only distances from that many sources picked with `seed` are measured.
+pub fn closeness(snapshot: &Snapshot, samples: Option<usize>, seed: u64) -> Centrality {
+    let n = snapshot.len();
+    let sources = sources(n, samples, seed);
+    let mut search = Search::new(n);
+    let mut reached = vec![0u64; n];
+    let mut distances = vec![0u64; n];
+
+    for &source in &sources {
+        search.run(snapshot, source);
+        for &node in &search.order[1..] {
+            reached[node as usize] += 1;
+            distances[node as usize] += search.distances[node as usize] as u64;
+        }
+    }
+
+    let scale = n as f64 / sources.len().max(1) as f64;
+    let scores = reached
+        .iter()
+        .zip(&distances)
+        .map(|(&reached, &distances)| {
+            if distances == 0 {
+                return 0.0;
+            }
+            let reached = reached as f64 * scale;
+            reached * reached / ((n - 1) as f64 * distances as f64 * scale)
+        })
+        .collect();
+    Centrality {
+        ranking: Ranking::new(snapshot, scores),
+        samples: sources.len(),
+    }
+}
+
+/// Computes the harmonic centrality of every node, the sum of the inverse distances to it from
+/// all other nodes, so that nodes out of reach add nothing. Given `samples`, only distances from
+/// that many sources picked with `seed` are measured, and the sums are scaled up accordingly.
+pub fn harmonic(snapshot: &Snapshot, samples: Option<usize>, seed: u64) -> Centrality {
+    let n = snapshot.len();
+    let sources = sources(n, samples, seed);
+    let mut search = Search::new(n);
+    let mut scores = vec![0.0; n];
+
+    for &source in &sources {
+        search.run(snapshot, source);
+        for &node in &search.order[1..] {
+            scores[node as usize] += 1.0 / search.distances[node as usize] as f64;
+        }
+    }
+
+    let scale = n as f64 / sources.len().max(1) as f64;
+    for score in &mut scores {
+        *score *= scale;
+    }
+    Centrality {
+        ranking: Ranking::new(snapshot, scores),
+        samples: sources.len(),
+    }
+}
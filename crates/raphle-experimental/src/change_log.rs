@@ -0,0 +1,156 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::node_id::{NodeId, NodeSet};
+use crate::relation::RelationId;
+use crate::rwlocked_graph::GraphAction;
+
+/// A single applied change to the graph.
+#[derive(Clone, Copy, Debug)]
+pub struct ChangeLogEntry {
+    /// Unix time in seconds at which the change was applied.
+    pub time: u64,
+    pub action: GraphAction,
+    pub source: NodeId,
+    pub target: NodeId,
+    pub relation: RelationId,
+    /// Whether the edge existed before the change, so the change can be undone exactly.
+    pub existed: bool,
+}
+
+/// How long flushed changes are kept by default, in seconds.
+pub const DEFAULT_RETENTION_SECS: u64 = 24 * 60 * 60;
+
+/// Time-ordered log of the changes applied after the graph was loaded. Edges loaded from an
+/// input file form the base of the log and are only tracked by the nodes they touch.
+///
+/// Changes are dropped from the front of the log once they are flushed and older than the
+/// retention. Offsets keep counting from the first change ever logged.
+pub struct ChangeLog {
+    entries: Vec<ChangeLogEntry>,
+    /// Offset of the first entry still kept.
+    start: usize,
+    flushed: usize,
+    base_nodes: NodeSet,
+    retention: u64,
+    /// Time of the newest dropped change; history from then on is complete.
+    truncated_at: u64,
+}
+
+impl Default for ChangeLog {
+    fn default() -> Self {
+        Self::with_retention(DEFAULT_RETENTION_SECS)
+    }
+}
+
+impl ChangeLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a log that keeps flushed changes for `retention` seconds.
+    pub fn with_retention(retention: u64) -> Self {
+        ChangeLog {
+            entries: Vec::new(),
+            start: 0,
+            flushed: 0,
+            base_nodes: NodeSet::new(),
+            retention,
+            truncated_at: 0,
+        }
+    }
+
+    /// How long flushed changes are kept, in seconds.
+    pub fn retention(&self) -> u64 {
+        self.retention
+    }
+
+    /// Appends a change. Times never go backwards, even if the wall clock does.
+    pub fn record(
+        &mut self,
+        action: GraphAction,
+        source: NodeId,
+        target: NodeId,
+        relation: RelationId,
+        existed: bool,
+    ) {
+        let time = self
+            .entries
+            .last()
+            .map_or(self.truncated_at, |last| last.time)
+            .max(now());
+        self.entries.push(ChangeLogEntry {
+            time,
+            action,
+            source,
+            target,
+            relation,
+            existed,
+        });
+    }
+
+    /// Marks a node touched by a bulk load, which is not logged edge by edge, or otherwise
+    /// changed outside the log, so that it is flushed.
+    pub fn mark_base(&mut self, nid: NodeId) {
+        self.base_nodes.insert(nid);
+    }
+
+    /// Returns the entries applied after `time`, oldest first. Entries that were dropped are
+    /// left out, see [`ChangeLog::covers`].
+    pub fn entries_after(&self, time: u64) -> &[ChangeLogEntry] {
+        let start = self.entries.partition_point(|entry| entry.time <= time);
+        &self.entries[start..]
+    }
+
+    /// Returns the entries from position `offset` in the log onwards.
+    pub fn entries_from(&self, offset: usize) -> &[ChangeLogEntry] {
+        let offset = offset.saturating_sub(self.start);
+        &self.entries[offset.min(self.entries.len())..]
+    }
+
+    /// Returns whether every change applied after `time` is still kept.
+    pub fn covers(&self, time: u64) -> bool {
+        time >= self.truncated_at
+    }
+
+    /// Offset just past the newest change, counting the changes that were dropped.
+    pub fn len(&self) -> usize {
+        self.start + self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Nodes changed since the last flush.
+    pub fn unflushed_nodes(&self) -> NodeSet {
+        let mut nodes = self.base_nodes.clone();
+        for entry in self.entries_from(self.flushed) {
+            nodes.insert(entry.source);
+            nodes.insert(entry.target);
+        }
+        nodes
+    }
+
+    /// Marks every change up to `offset` as persisted, and drops those of them older than the
+    /// retention.
+    pub fn mark_flushed(&mut self, offset: usize) {
+        self.flushed = offset;
+        self.base_nodes.clear();
+
+        let cutoff = now().saturating_sub(self.retention);
+        let flushed = offset.saturating_sub(self.start).min(self.entries.len());
+        let expired = self.entries[..flushed].partition_point(|entry| entry.time < cutoff);
+        if expired > 0 {
+            self.truncated_at = self.entries[expired - 1].time;
+            self.entries.drain(..expired);
+            self.start += expired;
+        }
+    }
+}
+
+/// Current unix time in seconds.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
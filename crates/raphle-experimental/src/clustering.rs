@@ -0,0 +1,109 @@
+use roaring::RoaringBitmap;
+
+use crate::node_id::{NodeId, NodeSet};
+use crate::relation::RelationId;
+use crate::rwlocked_graph::{AdjacencyView, Direction, RwLockedGraph};
+use crate::snapshot::Snapshot;
+
+/// Triangles through a single node, with edges taken in either direction.
+#[derive(Clone, Copy, Debug)]
+pub struct NodeClustering {
+    /// Number of distinct neighbors, the node itself left out.
+    pub degree: u64,
+    /// Number of edges between neighbors of the node.
+    pub triangles: u64,
+    /// Share of the pairs of neighbors that are linked, zero for fewer than two neighbors.
+    pub coefficient: f64,
+}
+
+/// Triangles over a whole graph, with edges taken in either direction.
+#[derive(Clone, Copy, Debug)]
+pub struct Clustering {
+    pub triangles: u64,
+    /// Share of the paths of two edges that are closed into a triangle.
+    pub transitivity: f64,
+    /// Mean of the local coefficients over all nodes.
+    pub average_coefficient: f64,
+}
+
+fn coefficient(degree: u64, triangles: u64) -> f64 {
+    if degree < 2 {
+        return 0.0;
+    }
+    2.0 * triangles as f64 / (degree * (degree - 1)) as f64
+}
+
+fn undirected_neighbors(adjacency: &AdjacencyView, node: NodeId, relation: RelationId) -> NodeSet {
+    let mut neighbors = adjacency.neighbors(node, relation, Direction::Outgoing);
+    neighbors |= adjacency.neighbors(node, relation, Direction::Incoming);
+    neighbors.remove(node);
+    neighbors
+}
+
+/// Counts the triangles through a node by intersecting its neighbor set with that of each of
+/// its neighbors, under a single read lock.
+pub fn node_clustering(
+    graph: &RwLockedGraph,
+    node: NodeId,
+    relation: RelationId,
+) -> NodeClustering {
+    let adjacency = graph.read_adjacency();
+    let neighbors = undirected_neighbors(&adjacency, node, relation);
+
+    // every edge between two neighbors is seen from both ends
+    let linked: u64 = neighbors
+        .iter()
+        .map(|neighbor| {
+            neighbors.intersection_len(&undirected_neighbors(&adjacency, neighbor, relation))
+        })
+        .sum();
+    let degree = neighbors.len();
+    let triangles = linked / 2;
+
+    NodeClustering {
+        degree,
+        triangles,
+        coefficient: coefficient(degree, triangles),
+    }
+}
+
+/// Counts the triangles of a whole snapshot, intersecting the neighbor sets of the ends of
+/// every edge.
+pub fn clustering(snapshot: &Snapshot) -> Clustering {
+    let n = snapshot.len();
+    let neighbors: Vec<RoaringBitmap> = (0..n)
+        .map(|i| {
+            let mut neighbors: RoaringBitmap = snapshot.outgoing(i).iter().copied().collect();
+            neighbors.extend(snapshot.incoming(i).iter().copied());
+            neighbors.remove(i as u32);
+            neighbors
+        })
+        .collect();
+
+    let mut closed = 0;
+    let mut triads = 0;
+    let mut coefficients = 0.0;
+    for node in &neighbors {
+        let linked: u64 = node
+            .iter()
+            .map(|neighbor| node.intersection_len(&neighbors[neighbor as usize]))
+            .sum();
+        let triangles = linked / 2;
+        let degree = node.len();
+
+        closed += triangles;
+        triads += degree * degree.saturating_sub(1) / 2;
+        coefficients += coefficient(degree, triangles);
+    }
+
+    Clustering {
+        // every triangle is closed at each of its three nodes
+        triangles: closed / 3,
+        transitivity: if triads == 0 {
+            0.0
+        } else {
+            closed as f64 / triads as f64
+        },
+        average_coefficient: if n == 0 { 0.0 } else { coefficients / n as f64 },
+    }
+}
@@ -0,0 +1,286 @@
+use hashbrown::HashMap;
+use serde::Deserialize;
+
+use crate::components::Components;
+use crate::rng::Rng;
+use crate::snapshot::Snapshot;
+
+/// Smallest gain in modularity worth moving a node for, which keeps rounding errors from moving
+/// nodes back and forth forever.
+const MIN_GAIN: f64 = 1e-12;
+
+/// Most passes over all nodes before an algorithm settles for what it has.
+const MAX_PASSES: usize = 100;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommunityAlgorithm {
+    /// Louvain modularity optimization.
+    #[default]
+    Louvain,
+    /// Label propagation, faster but coarser.
+    LabelPropagation,
+}
+
+pub struct Communities {
+    pub partition: Components,
+    /// Modularity of the partition, with edges taken as undirected.
+    pub modularity: f64,
+}
+
+/// An undirected weighted graph, with the weight of self-loops kept apart.
+#[derive(Clone)]
+struct WeightedGraph {
+    adjacency: Vec<Vec<(u32, f64)>>,
+    self_loops: Vec<f64>,
+}
+
+impl WeightedGraph {
+    /// Takes the edges of a snapshot as undirected. Edges in both directions between two nodes
+    /// add up.
+    fn from_snapshot(snapshot: &Snapshot) -> Self {
+        let n = snapshot.len();
+        let mut weights: Vec<HashMap<u32, f64>> = vec![HashMap::new(); n];
+        let mut self_loops = vec![0.0; n];
+        for i in 0..n {
+            let edge_weights = snapshot.outgoing_weights(i);
+            for (k, &j) in snapshot.outgoing(i).iter().enumerate() {
+                // undirected snapshots hold every edge from both ends
+                if !snapshot.is_directed() && (j as usize) < i {
+                    continue;
+                }
+                let weight = edge_weights.map_or(1.0, |weights| weights[k]);
+                if j as usize == i {
+                    self_loops[i] += weight;
+                } else {
+                    *weights[i].entry(j).or_insert(0.0) += weight;
+                    *weights[j as usize].entry(i as u32).or_insert(0.0) += weight;
+                }
+            }
+        }
+
+        WeightedGraph {
+            adjacency: weights.into_iter().map(sorted_edges).collect(),
+            self_loops,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    /// Weighted degree of a node, counting its self-loop from both ends.
+    fn degree(&self, i: usize) -> f64 {
+        self.adjacency[i].iter().map(|&(_, w)| w).sum::<f64>() + 2.0 * self.self_loops[i]
+    }
+
+    fn modularity(&self, labels: &[u32]) -> f64 {
+        let n = self.len();
+        let degrees: Vec<f64> = (0..n).map(|i| self.degree(i)).collect();
+        let total: f64 = degrees.iter().sum();
+        if total == 0.0 {
+            return 0.0;
+        }
+
+        let mut inside = vec![0.0; n];
+        let mut totals = vec![0.0; n];
+        for i in 0..n {
+            let community = labels[i] as usize;
+            totals[community] += degrees[i];
+            inside[community] += 2.0 * self.self_loops[i];
+            for &(j, w) in &self.adjacency[i] {
+                if labels[j as usize] as usize == community {
+                    inside[community] += w;
+                }
+            }
+        }
+        inside
+            .iter()
+            .zip(&totals)
+            .map(|(inside, total_degree)| inside / total - (total_degree / total).powi(2))
+            .sum()
+    }
+
+    /// Merges the nodes of each community into a single node, their internal edges into its
+    /// self-loop.
+    fn aggregate(&self, labels: &[u32], count: usize) -> WeightedGraph {
+        let mut weights: Vec<HashMap<u32, f64>> = vec![HashMap::new(); count];
+        let mut self_loops = vec![0.0; count];
+        for i in 0..self.len() {
+            let community = labels[i];
+            self_loops[community as usize] += self.self_loops[i];
+            for &(j, w) in &self.adjacency[i] {
+                let other = labels[j as usize];
+                if other == community {
+                    // seen from both ends
+                    self_loops[community as usize] += w / 2.0;
+                } else {
+                    *weights[community as usize].entry(other).or_insert(0.0) += w;
+                }
+            }
+        }
+
+        WeightedGraph {
+            adjacency: weights.into_iter().map(sorted_edges).collect(),
+            self_loops,
+        }
+    }
+}
+
+fn sorted_edges(weights: HashMap<u32, f64>) -> Vec<(u32, f64)> {
+    let mut edges: Vec<(u32, f64)> = weights.into_iter().collect();
+    edges.sort_unstable_by_key(|&(j, _)| j);
+    edges
+}
+
+/// Numbers labels from zero in order of first appearance, returning the number of labels.
+fn renumber(labels: &mut [u32]) -> usize {
+    let mut numbers: HashMap<u32, u32> = HashMap::new();
+    for label in labels.iter_mut() {
+        let next = numbers.len() as u32;
+        *label = *numbers.entry(*label).or_insert(next);
+    }
+    numbers.len()
+}
+
+/// Moves nodes to the neighboring community with the largest gain in modularity until no move
+/// gains anything, returning the communities and whether any node moved.
+fn move_nodes(graph: &WeightedGraph, rng: &mut Rng) -> (Vec<u32>, bool) {
+    let n = graph.len();
+    let mut labels: Vec<u32> = (0..n as u32).collect();
+    let degrees: Vec<f64> = (0..n).map(|i| graph.degree(i)).collect();
+    let total: f64 = degrees.iter().sum();
+    if total == 0.0 {
+        return (labels, false);
+    }
+
+    let mut totals = degrees.clone();
+    let mut weights_to = vec![0.0; n];
+    let mut seen = vec![false; n];
+    let mut neighbors: Vec<u32> = Vec::new();
+    let mut order: Vec<usize> = (0..n).collect();
+    rng.shuffle(&mut order);
+
+    let mut moved_any = false;
+    for _ in 0..MAX_PASSES {
+        let mut moved = false;
+        for &i in &order {
+            let current = labels[i];
+            for &(j, w) in &graph.adjacency[i] {
+                let community = labels[j as usize];
+                if !seen[community as usize] {
+                    seen[community as usize] = true;
+                    neighbors.push(community);
+                }
+                weights_to[community as usize] += w;
+            }
+
+            totals[current as usize] -= degrees[i];
+            let gain = |community: u32| {
+                weights_to[community as usize] - totals[community as usize] * degrees[i] / total
+            };
+            let mut best = current;
+            let mut best_gain = gain(current);
+            for &community in &neighbors {
+                let community_gain = gain(community);
+                if community_gain > best_gain + MIN_GAIN {
+                    best = community;
+                    best_gain = community_gain;
+                }
+            }
+            totals[best as usize] += degrees[i];
+            labels[i] = best;
+            moved |= best != current;
+
+            for community in neighbors.drain(..) {
+                seen[community as usize] = false;
+                weights_to[community as usize] = 0.0;
+            }
+        }
+        if !moved {
+            break;
+        }
+        moved_any = true;
+    }
+    (labels, moved_any)
+}
+
+/// Runs Louvain: moves nodes between communities while modularity improves, merges each
+/// community into a node, and repeats on the merged graph until nothing moves.
+fn louvain(graph: &WeightedGraph, rng: &mut Rng) -> Vec<u32> {
+    let mut assignment: Vec<u32> = (0..graph.len() as u32).collect();
+    let mut level = graph.clone();
+    for _ in 0..MAX_PASSES {
+        let (mut labels, moved) = move_nodes(&level, rng);
+        if !moved {
+            break;
+        }
+        let count = renumber(&mut labels);
+        for community in assignment.iter_mut() {
+            *community = labels[*community as usize];
+        }
+        level = level.aggregate(&labels, count);
+    }
+    assignment
+}
+
+/// Runs label propagation: every node takes the label carrying the most edge weight among its
+/// neighbors, keeping its own on ties and otherwise breaking them at random, until no label
+/// changes.
+fn label_propagation(graph: &WeightedGraph, rng: &mut Rng) -> Vec<u32> {
+    let n = graph.len();
+    let mut labels: Vec<u32> = (0..n as u32).collect();
+    let mut order: Vec<usize> = (0..n).collect();
+    let mut weights: HashMap<u32, f64> = HashMap::new();
+    let mut best: Vec<u32> = Vec::new();
+
+    for _ in 0..MAX_PASSES {
+        rng.shuffle(&mut order);
+        let mut changed = false;
+        for &i in &order {
+            if graph.adjacency[i].is_empty() {
+                continue;
+            }
+            weights.clear();
+            for &(j, w) in &graph.adjacency[i] {
+                *weights.entry(labels[j as usize]).or_insert(0.0) += w;
+            }
+            let max = weights.values().copied().fold(f64::MIN, f64::max);
+
+            best.clear();
+            best.extend(
+                weights
+                    .iter()
+                    .filter(|(_, &w)| w >= max - MIN_GAIN)
+                    .map(|(&label, _)| label),
+            );
+            if best.contains(&labels[i]) {
+                continue;
+            }
+            // hash map order is not seeded, so sort before picking
+            best.sort_unstable();
+            labels[i] = best[rng.below(best.len() as u64) as usize];
+            changed = true;
+        }
+        if !changed {
+            break;
+        }
+    }
+    labels
+}
+
+/// Detects communities in a snapshot, with its edges taken as undirected and weighted by the
+/// snapshot's weights, if any. Runs are reproducible for a given `seed`.
+pub fn communities(snapshot: &Snapshot, algorithm: CommunityAlgorithm, seed: u64) -> Communities {
+    let graph = WeightedGraph::from_snapshot(snapshot);
+    let mut rng = Rng::new(seed);
+    let labels = match algorithm {
+        CommunityAlgorithm::Louvain => louvain(&graph, &mut rng),
+        CommunityAlgorithm::LabelPropagation => label_propagation(&graph, &mut rng),
+    };
+
+    Communities {
+        modularity: graph.modularity(&labels),
+        partition: Components::new(snapshot, labels),
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+
+use crate::node_id::{NodeId, NodeSet};
+use crate::snapshot::Snapshot;
+
+/// Marks nodes not reached yet by a search.
+const UNVISITED: u32 = u32::MAX;
+
+/// A partition of the nodes of a snapshot into components, or communities. Components are
+/// numbered from zero in the order of their lowest node ID.
+pub struct Components {
+    nodes: Vec<NodeId>,
+    /// Component of each node, by index.
+    assignment: Vec<u32>,
+    sizes: Vec<u64>,
+}
+
+impl Components {
+    /// Numbers the components in the order they are first seen by node index, whatever labels
+    /// they were found under.
+    pub(crate) fn new(snapshot: &Snapshot, labels: Vec<u32>) -> Self {
+        let mut renumbered = vec![UNVISITED; labels.len()];
+        let mut sizes = Vec::new();
+        let mut assignment = Vec::with_capacity(labels.len());
+        for &label in &labels {
+            let component = &mut renumbered[label as usize];
+            if *component == UNVISITED {
+                *component = sizes.len() as u32;
+                sizes.push(0);
+            }
+            sizes[*component as usize] += 1;
+            assignment.push(*component);
+        }
+
+        Components {
+            nodes: (0..snapshot.len()).map(|i| snapshot.node(i)).collect(),
+            assignment,
+            sizes,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// Returns the component of a node, if it is in the snapshot.
+    pub fn component_of(&self, nid: NodeId) -> Option<u32> {
+        let index = self.nodes.binary_search(&nid).ok()?;
+        Some(self.assignment[index])
+    }
+
+    pub fn size(&self, component: u32) -> u64 {
+        self.sizes[component as usize]
+    }
+
+    /// Returns the nodes of a component.
+    pub fn members(&self, component: u32) -> NodeSet {
+        self.assignment
+            .iter()
+            .zip(&self.nodes)
+            .filter(|(&assigned, _)| assigned == component)
+            .map(|(_, &nid)| nid)
+            .collect()
+    }
+
+    /// Returns the number of components of each size.
+    pub fn size_histogram(&self) -> BTreeMap<u64, u64> {
+        let mut histogram = BTreeMap::new();
+        for &size in &self.sizes {
+            *histogram.entry(size).or_insert(0) += 1;
+        }
+        histogram
+    }
+}
+
+/// Finds the weakly connected components of a snapshot, the nodes linked by edges in either
+/// direction, with a union-find over all edges.
+pub fn weakly_connected(snapshot: &Snapshot) -> Components {
+    let n = snapshot.len();
+    let mut parents: Vec<u32> = (0..n as u32).collect();
+    let mut sizes = vec![1u32; n];
+
+    fn find(parents: &mut [u32], mut node: u32) -> u32 {
+        while parents[node as usize] != node {
+            // path halving
+            parents[node as usize] = parents[parents[node as usize] as usize];
+            node = parents[node as usize];
+        }
+        node
+    }
+
+    for source in 0..n {
+        for &target in snapshot.outgoing(source) {
+            let a = find(&mut parents, source as u32);
+            let b = find(&mut parents, target);
+            if a == b {
+                continue;
+            }
+            let (small, large) = if sizes[a as usize] < sizes[b as usize] {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            parents[small as usize] = large;
+            sizes[large as usize] += sizes[small as usize];
+        }
+    }
+
+    let labels = (0..n as u32).map(|node| find(&mut parents, node)).collect();
+    Components::new(snapshot, labels)
+}
+
+/// Finds the strongly connected components of a snapshot, the nodes that can all reach each
+/// other along outgoing edges, with Tarjan's algorithm. The search keeps its own stack rather
+/// than recursing, so long paths cannot overflow the thread's stack.
+pub fn strongly_connected(snapshot: &Snapshot) -> Components {
+    let n = snapshot.len();
+    let mut order = vec![UNVISITED; n];
+    let mut lowlinks = vec![0u32; n];
+    let mut on_stack = vec![false; n];
+    let mut stack: Vec<u32> = Vec::new();
+    // nodes being searched, with the position of the next edge to follow
+    let mut calls: Vec<(u32, usize)> = Vec::new();
+    let mut labels = vec![UNVISITED; n];
+    let mut visited = 0;
+
+    for root in 0..n as u32 {
+        if order[root as usize] != UNVISITED {
+            continue;
+        }
+        order[root as usize] = visited;
+        lowlinks[root as usize] = visited;
+        visited += 1;
+        stack.push(root);
+        on_stack[root as usize] = true;
+        calls.push((root, 0));
+
+        while let Some((node, next_edge)) = calls.last_mut() {
+            let node = *node;
+            let edges = snapshot.outgoing(node as usize);
+            if let Some(&neighbor) = edges.get(*next_edge) {
+                *next_edge += 1;
+                if order[neighbor as usize] == UNVISITED {
+                    order[neighbor as usize] = visited;
+                    lowlinks[neighbor as usize] = visited;
+                    visited += 1;
+                    stack.push(neighbor);
+                    on_stack[neighbor as usize] = true;
+                    calls.push((neighbor, 0));
+                } else if on_stack[neighbor as usize] {
+                    lowlinks[node as usize] = lowlinks[node as usize].min(order[neighbor as usize]);
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(caller, _)) = calls.last() {
+                lowlinks[caller as usize] = lowlinks[caller as usize].min(lowlinks[node as usize]);
+            }
+            if lowlinks[node as usize] == order[node as usize] {
+                while let Some(member) = stack.pop() {
+                    on_stack[member as usize] = false;
+                    labels[member as usize] = node;
+                    if member == node {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    Components::new(snapshot, labels)
+}
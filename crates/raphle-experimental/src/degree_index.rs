@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+
+use hashbrown::HashMap;
+
+use crate::node_id::{NodeId, NodeSet};
+use crate::relation::RelationId;
+use crate::rwlocked_graph::Direction;
+
+/// Nodes bucketed by their number of edges of each relation and direction, kept up to date as
+/// edges come and go, so the nodes of highest degree and the distribution of degrees are read
+/// without visiting every node. Nodes without edges are not indexed.
+#[derive(Default)]
+pub struct DegreeIndex {
+    buckets: HashMap<(RelationId, Direction), BTreeMap<u64, NodeSet>>,
+}
+
+impl DegreeIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves a node from one degree to another after it gained or lost edges.
+    pub fn update(
+        &mut self,
+        node: NodeId,
+        relation: RelationId,
+        direction: Direction,
+        from: u64,
+        to: u64,
+    ) {
+        let buckets = self.buckets.entry((relation, direction)).or_default();
+        if from > 0 {
+            if let Some(bucket) = buckets.get_mut(&from) {
+                bucket.remove(node);
+                if bucket.is_empty() {
+                    buckets.remove(&from);
+                }
+            }
+        }
+        if to > 0 {
+            buckets.entry(to).or_default().insert(node);
+        }
+    }
+
+    /// Returns up to `limit` nodes of highest degree with their degrees, ties broken by lower
+    /// node ID.
+    pub fn top(
+        &self,
+        relation: RelationId,
+        direction: Direction,
+        limit: usize,
+    ) -> Vec<(NodeId, u64)> {
+        let Some(buckets) = self.buckets.get(&(relation, direction)) else {
+            return Vec::new();
+        };
+        buckets
+            .iter()
+            .rev()
+            .flat_map(|(&degree, nodes)| nodes.iter().map(move |nid| (nid, degree)))
+            .take(limit)
+            .collect()
+    }
+
+    /// Returns the number of nodes of each degree.
+    pub fn histogram(&self, relation: RelationId, direction: Direction) -> BTreeMap<u64, u64> {
+        self.buckets
+            .get(&(relation, direction))
+            .map_or_else(BTreeMap::new, |buckets| {
+                buckets
+                    .iter()
+                    .map(|(&degree, nodes)| (degree, nodes.len()))
+                    .collect()
+            })
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::node_data::PropertyValue;
+use crate::node_id::NodeId;
+use crate::relation::RelationId;
+
+/// Identifies a single edge as (source, target, relation).
+pub type EdgeKey = (NodeId, NodeId, RelationId);
+
+/// Per-edge metadata that the adjacency bitmaps cannot carry. Values are stored column by
+/// column, so a property that only some edges have costs nothing for the others.
+#[derive(Default)]
+pub struct EdgePropertyStore {
+    columns: HashMap<String, HashMap<EdgeKey, PropertyValue>>,
+    dirty: HashSet<EdgeKey>,
+}
+
+impl EdgePropertyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, edge: EdgeKey, key: &str, value: PropertyValue) {
+        self.columns.entry_ref(key).or_default().insert(edge, value);
+        self.dirty.insert(edge);
+    }
+
+    /// Returns a single property of an edge.
+    pub fn get(&self, edge: &EdgeKey, key: &str) -> Option<&PropertyValue> {
+        self.columns.get(key).and_then(|column| column.get(edge))
+    }
+
+    /// Returns all properties of an edge, sorted by key.
+    pub fn get_all(&self, edge: &EdgeKey) -> BTreeMap<String, PropertyValue> {
+        self.columns
+            .iter()
+            .filter_map(|(key, column)| column.get(edge).map(|value| (key.clone(), value.clone())))
+            .collect()
+    }
+
+    /// Drops every property of an edge, e.g. once the edge is removed.
+    pub fn remove_all(&mut self, edge: &EdgeKey) {
+        let mut removed = false;
+        for column in self.columns.values_mut() {
+            removed |= column.remove(edge).is_some();
+        }
+        if removed {
+            self.dirty.insert(*edge);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.values().all(|column| column.is_empty())
+    }
+
+    /// Edges whose properties changed since the last flush.
+    pub fn dirty(&self) -> &HashSet<EdgeKey> {
+        &self.dirty
+    }
+
+    pub fn clear_dirty(&mut self) {
+        self.dirty.clear();
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeSet;
+
+use hashbrown::HashMap;
+
+use crate::edge_properties::EdgeKey;
+use crate::node_id::NodeId;
+use crate::relation::RelationId;
+use crate::rwlocked_graph::Direction;
+
+/// Expiry times of temporary edges, indexed by expiry for the reaper and by node so reads can
+/// hide edges that expired but have not been reaped yet.
+#[derive(Default)]
+pub struct ExpiryIndex {
+    expiries: HashMap<EdgeKey, u64>,
+    queue: BTreeSet<(u64, EdgeKey)>,
+    outgoing: HashMap<(NodeId, RelationId), HashMap<NodeId, u64>>,
+    incoming: HashMap<(NodeId, RelationId), HashMap<NodeId, u64>>,
+}
+
+impl ExpiryIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the unix time, in seconds, at which an edge expires.
+    pub fn set(&mut self, edge: EdgeKey, expires_at: u64) {
+        self.clear(&edge);
+
+        let (source, target, relation) = edge;
+        self.expiries.insert(edge, expires_at);
+        self.queue.insert((expires_at, edge));
+        self.outgoing
+            .entry((source, relation))
+            .or_default()
+            .insert(target, expires_at);
+        self.incoming
+            .entry((target, relation))
+            .or_default()
+            .insert(source, expires_at);
+    }
+
+    /// Makes an edge permanent again, e.g. once it is removed or re-added without a TTL.
+    pub fn clear(&mut self, edge: &EdgeKey) {
+        let Some(expires_at) = self.expiries.remove(edge) else {
+            return;
+        };
+
+        let (source, target, relation) = *edge;
+        self.queue.remove(&(expires_at, *edge));
+        if let Some(targets) = self.outgoing.get_mut(&(source, relation)) {
+            targets.remove(&target);
+            if targets.is_empty() {
+                self.outgoing.remove(&(source, relation));
+            }
+        }
+        if let Some(sources) = self.incoming.get_mut(&(target, relation)) {
+            sources.remove(&source);
+            if sources.is_empty() {
+                self.incoming.remove(&(target, relation));
+            }
+        }
+    }
+
+    /// Returns the unix time at which an edge expires, if it is temporary.
+    pub fn expires_at(&self, edge: &EdgeKey) -> Option<u64> {
+        self.expiries.get(edge).copied()
+    }
+
+    /// Returns whether an edge has expired by `now`.
+    pub fn is_expired(&self, edge: &EdgeKey, now: u64) -> bool {
+        self.expires_at(edge)
+            .is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// Returns the neighbors of a node whose edges have expired by `now`.
+    pub fn expired_neighbors(
+        &self,
+        node: NodeId,
+        relation: RelationId,
+        direction: Direction,
+        now: u64,
+    ) -> Vec<NodeId> {
+        let index = match direction {
+            Direction::Outgoing => &self.outgoing,
+            Direction::Incoming => &self.incoming,
+        };
+        index
+            .get(&(node, relation))
+            .into_iter()
+            .flatten()
+            .filter(|(_, &expires_at)| expires_at <= now)
+            .map(|(&neighbor, _)| neighbor)
+            .collect()
+    }
+
+    /// Returns every edge that has expired by `now`, earliest first.
+    pub fn expired(&self, now: u64) -> Vec<EdgeKey> {
+        self.queue
+            .iter()
+            .take_while(|(expires_at, _)| *expires_at <= now)
+            .map(|(_, edge)| *edge)
+            .collect()
+    }
+
+    /// Returns every temporary edge along with the unix time at which it expires.
+    pub fn iter(&self) -> impl Iterator<Item = (EdgeKey, u64)> + '_ {
+        self.expiries
+            .iter()
+            .map(|(&edge, &expires_at)| (edge, expires_at))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expiries.is_empty()
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt;
+
+use hashbrown::HashMap;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::node_id::{NodeId, NodeSet};
+
+/// External identifier of a node. Keys made only of digits are always treated as
+/// [`NodeKey::Int`], so `"42"` and `42` refer to the same node.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NodeKey {
+    Int(u64),
+    Str(String),
+}
+
+impl From<&str> for NodeKey {
+    fn from(raw: &str) -> Self {
+        match raw.parse::<u64>() {
+            Ok(id) => NodeKey::Int(id),
+            Err(_) => NodeKey::Str(raw.to_string()),
+        }
+    }
+}
+
+impl From<u64> for NodeKey {
+    fn from(id: u64) -> Self {
+        NodeKey::Int(id)
+    }
+}
+
+impl fmt::Display for NodeKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeKey::Int(id) => write!(f, "{}", id),
+            NodeKey::Str(key) => write!(f, "{}", key),
+        }
+    }
+}
+
+impl Serialize for NodeKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            NodeKey::Int(id) => serializer.serialize_u64(*id),
+            NodeKey::Str(key) => serializer.serialize_str(key),
+        }
+    }
+}
+
+struct NodeKeyVisitor;
+
+impl<'de> de::Visitor<'de> for NodeKeyVisitor {
+    type Value = NodeKey;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a non-negative integer or a string node key")
+    }
+
+    fn visit_u64<E: de::Error>(self, id: u64) -> Result<NodeKey, E> {
+        Ok(NodeKey::Int(id))
+    }
+
+    fn visit_i64<E: de::Error>(self, id: i64) -> Result<NodeKey, E> {
+        u64::try_from(id)
+            .map(NodeKey::Int)
+            .map_err(|_| E::custom("node keys cannot be negative"))
+    }
+
+    fn visit_str<E: de::Error>(self, raw: &str) -> Result<NodeKey, E> {
+        Ok(NodeKey::from(raw))
+    }
+}
+
+// Query strings only ever hand us strings, so numbers are recovered in `visit_str`.
+impl<'de> Deserialize<'de> for NodeKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<NodeKey, D::Error> {
+        deserializer.deserialize_any(NodeKeyVisitor)
+    }
+}
+
+/// Assigns dense internal [`NodeId`]s to external [`NodeKey`]s. Dense IDs keep the adjacency
+/// bitmaps small, since neighbouring IDs share roaring containers.
+#[derive(Clone)]
+pub struct IdMap {
+    to_internal: HashMap<NodeKey, NodeId>,
+    to_external: Vec<NodeKey>,
+    unflushed: NodeSet,
+}
+
+impl IdMap {
+    pub fn new(expected_node_count: u32) -> Self {
+        IdMap {
+            to_internal: HashMap::with_capacity(expected_node_count as usize),
+            to_external: Vec::with_capacity(expected_node_count as usize),
+            unflushed: NodeSet::new(),
+        }
+    }
+
+    /// Returns the internal ID of a key, if one has been assigned.
+    pub fn get(&self, key: &NodeKey) -> Option<NodeId> {
+        self.to_internal.get(key).copied()
+    }
+
+    /// Returns the internal ID of a key, assigning the next free one if the key is new.
+    pub fn get_or_insert(&mut self, key: &NodeKey) -> NodeId {
+        if let Some(&nid) = self.to_internal.get(key) {
+            return nid;
+        }
+
+        let nid =
+            NodeId::try_from(self.to_external.len()).expect("internal node ID space exhausted");
+        self.to_internal.insert(key.clone(), nid);
+        self.to_external.push(key.clone());
+        self.unflushed.insert(nid);
+        nid
+    }
+
+    /// Returns the external key of an internal ID.
+    pub fn key(&self, nid: NodeId) -> Option<&NodeKey> {
+        self.to_external.get(nid as usize)
+    }
+
+    pub fn len(&self) -> usize {
+        self.to_external.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.to_external.is_empty()
+    }
+
+    /// Internal IDs assigned since the mapping was last persisted.
+    pub fn unflushed(&self) -> &NodeSet {
+        &self.unflushed
+    }
+
+    pub fn clear_unflushed(&mut self) {
+        self.unflushed.clear();
+    }
+}
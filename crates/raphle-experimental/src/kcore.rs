@@ -0,0 +1,160 @@
+use serde::Deserialize;
+
+use crate::node_id::{NodeId, NodeSet};
+use crate::snapshot::Snapshot;
+
+/// Which edges count toward the degree of a node when peeling cores.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DegreeMode {
+    /// Distinct neighbors in either direction, treating the graph as undirected.
+    #[default]
+    Total,
+    /// Incoming edges only.
+    In,
+    /// Outgoing edges only.
+    Out,
+}
+
+/// The core number of every node of a snapshot: the largest `k` for which the node belongs to
+/// the k-core, the largest subgraph in which every node has a degree of at least `k`.
+pub struct Coreness {
+    nodes: Vec<NodeId>,
+    cores: Vec<u32>,
+}
+
+impl Coreness {
+    /// Returns the core number of a node, if it is in the snapshot.
+    pub fn core_of(&self, nid: NodeId) -> Option<u32> {
+        let index = self.nodes.binary_search(&nid).ok()?;
+        Some(self.cores[index])
+    }
+
+    /// Returns the highest core number of any node.
+    pub fn max_core(&self) -> u32 {
+        self.cores.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Returns the nodes of the k-core.
+    pub fn members(&self, k: u32) -> NodeSet {
+        self.cores
+            .iter()
+            .zip(&self.nodes)
+            .filter(|(&core, _)| core >= k)
+            .map(|(_, &nid)| nid)
+            .collect()
+    }
+}
+
+/// Merges the outgoing and incoming neighbors of every node into one sorted list of distinct
+/// neighbors, as compressed rows.
+fn undirected_neighbors(snapshot: &Snapshot) -> (Vec<usize>, Vec<u32>) {
+    let mut offsets = Vec::with_capacity(snapshot.len() + 1);
+    let mut neighbors = Vec::new();
+    offsets.push(0);
+    for i in 0..snapshot.len() {
+        let (outgoing, incoming) = (snapshot.outgoing(i), snapshot.incoming(i));
+        let (mut a, mut b) = (0, 0);
+        while a < outgoing.len() || b < incoming.len() {
+            let next = match (outgoing.get(a), incoming.get(b)) {
+                (Some(&x), Some(&y)) if x == y => {
+                    a += 1;
+                    b += 1;
+                    x
+                }
+                (Some(&x), Some(&y)) if x < y => {
+                    a += 1;
+                    x
+                }
+                (Some(&x), None) => {
+                    a += 1;
+                    x
+                }
+                (_, Some(&y)) => {
+                    b += 1;
+                    y
+                }
+                (None, None) => unreachable!(),
+            };
+            neighbors.push(next);
+        }
+        offsets.push(neighbors.len());
+    }
+    (offsets, neighbors)
+}
+
+/// Computes the core number of every node by repeatedly peeling off the node of lowest
+/// remaining degree, in linear time with the bucket algorithm of Batagelj and Zaversnik.
+/// Self-loops are ignored. With [`DegreeMode::In`], removing a node lowers the degree of the
+/// nodes it has edges to; with [`DegreeMode::Out`], that of the nodes with edges to it.
+pub fn coreness(snapshot: &Snapshot, mode: DegreeMode) -> Coreness {
+    let n = snapshot.len();
+    let undirected = match mode {
+        DegreeMode::Total => Some(undirected_neighbors(snapshot)),
+        DegreeMode::In | DegreeMode::Out => None,
+    };
+    let undirected = |i: usize| -> &[u32] {
+        let (offsets, neighbors) = undirected.as_ref().unwrap();
+        &neighbors[offsets[i]..offsets[i + 1]]
+    };
+    // the neighbors counted in the degree of a node
+    let counted = |i: usize| match mode {
+        DegreeMode::Total => undirected(i),
+        DegreeMode::In => snapshot.incoming(i),
+        DegreeMode::Out => snapshot.outgoing(i),
+    };
+    // the neighbors whose degree drops when a node is removed
+    let lowered = |i: usize| match mode {
+        DegreeMode::Total => undirected(i),
+        DegreeMode::In => snapshot.outgoing(i),
+        DegreeMode::Out => snapshot.incoming(i),
+    };
+
+    let mut degrees: Vec<u32> = (0..n)
+        .map(|i| counted(i).iter().filter(|&&j| j as usize != i).count() as u32)
+        .collect();
+    let max_degree = degrees.iter().copied().max().unwrap_or(0) as usize;
+
+    // nodes sorted by degree, with the position in `sorted` where each degree starts
+    let mut starts = vec![0usize; max_degree + 2];
+    for &degree in &degrees {
+        starts[degree as usize + 1] += 1;
+    }
+    for d in 1..starts.len() {
+        starts[d] += starts[d - 1];
+    }
+    let mut sorted = vec![0u32; n];
+    let mut positions = vec![0usize; n];
+    let mut next = starts.clone();
+    for (i, &degree) in degrees.iter().enumerate() {
+        positions[i] = next[degree as usize];
+        sorted[positions[i]] = i as u32;
+        next[degree as usize] += 1;
+    }
+
+    for position in 0..n {
+        let node = sorted[position] as usize;
+        for &neighbor in lowered(node) {
+            let neighbor = neighbor as usize;
+            if neighbor == node || degrees[neighbor] <= degrees[node] {
+                continue;
+            }
+            // move the neighbor to the front of its degree's bucket, then shrink the bucket
+            let degree = degrees[neighbor] as usize;
+            let front = starts[degree];
+            let swapped = sorted[front] as usize;
+            if swapped != neighbor {
+                sorted.swap(front, positions[neighbor]);
+                positions[swapped] = positions[neighbor];
+                positions[neighbor] = front;
+            }
+            starts[degree] += 1;
+            degrees[neighbor] -= 1;
+        }
+    }
+
+    Coreness {
+        nodes: (0..n).map(|i| snapshot.node(i)).collect(),
+        cores: degrees,
+    }
+}
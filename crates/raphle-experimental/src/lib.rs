@@ -1 +1,23 @@
+pub mod batch;
+pub mod centrality;
+pub mod change_log;
+pub mod clustering;
+pub mod community;
+pub mod components;
+pub mod degree_index;
+pub mod edge_properties;
+pub mod expiry;
+pub mod id_map;
+pub mod kcore;
+pub mod neighbor_list;
+pub mod node_data;
+pub mod node_id;
+pub mod rank;
+pub mod recommend;
+pub mod relation;
+pub mod rng;
 pub mod rwlocked_graph;
+pub mod set_query;
+pub mod snapshot;
+pub mod traversal;
+pub mod weighted_path;
@@ -0,0 +1,121 @@
+use hashbrown::HashMap;
+
+use crate::node_id::{self, NodeId, NodeSet};
+use crate::rng::Rng;
+
+/// Narrows a listing to neighbors carrying a label, or linked by edges whose timestamp lies
+/// within a window.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NeighborFilter<'a> {
+    pub label: Option<&'a str>,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+}
+
+/// Which neighbors of a node to list.
+#[derive(Clone, Copy, Debug)]
+pub enum Selection {
+    /// Up to `limit` neighbors in order of ID, starting after the `after` cursor, from the
+    /// highest ID down if `reverse`.
+    Page {
+        after: Option<NodeId>,
+        limit: usize,
+        reverse: bool,
+    },
+    /// Up to `size` neighbors picked uniformly at random, the same ones for the same seed.
+    Sample { size: usize, seed: u64 },
+}
+
+#[derive(Debug, Default)]
+pub struct NeighborList {
+    pub nodes: Vec<NodeId>,
+    /// Cursor to pass as `after` for the next page, if there are more neighbors.
+    pub next: Option<NodeId>,
+}
+
+/// Lists the neighbors in a neighbor set that `keep` accepts, reading the set in place.
+pub(crate) fn select(
+    neighbors: &NodeSet,
+    selection: &Selection,
+    keep: impl Fn(NodeId) -> bool,
+) -> NeighborList {
+    match *selection {
+        Selection::Page {
+            after,
+            limit,
+            reverse,
+        } => page(neighbors, after, limit, reverse, keep),
+        Selection::Sample { size, seed } => NeighborList {
+            nodes: sample(neighbors, size, seed, keep),
+            next: None,
+        },
+    }
+}
+
+fn page(
+    neighbors: &NodeSet,
+    after: Option<NodeId>,
+    limit: usize,
+    reverse: bool,
+    keep: impl Fn(NodeId) -> bool,
+) -> NeighborList {
+    let mut iter = neighbors.iter();
+    if let Some(after) = after {
+        if reverse {
+            iter.advance_back_to(after);
+        } else {
+            iter.advance_to(after);
+        }
+    }
+    let iter: Box<dyn Iterator<Item = NodeId>> = if reverse {
+        Box::new(iter.rev())
+    } else {
+        Box::new(iter)
+    };
+
+    // one more than asked for tells whether there is a next page
+    let mut nodes: Vec<NodeId> = iter
+        .filter(|&nid| Some(nid) != after && keep(nid))
+        .take(limit.saturating_add(1))
+        .collect();
+    let next = if nodes.len() > limit {
+        nodes.truncate(limit);
+        nodes.last().copied()
+    } else {
+        None
+    };
+    NeighborList { nodes, next }
+}
+
+/// Samples neighbors by shuffling their ranks in the set lazily, so only as many ranks are
+/// drawn as it takes to find `size` neighbors that `keep` accepts. Returns them sorted.
+fn sample(
+    neighbors: &NodeSet,
+    size: usize,
+    seed: u64,
+    keep: impl Fn(NodeId) -> bool,
+) -> Vec<NodeId> {
+    let len = neighbors.len();
+    let mut rng = Rng::new(seed);
+    // ranks moved by the shuffle so far; every other rank is still in place
+    let mut swapped: HashMap<u64, u64> = HashMap::new();
+    let mut nodes = Vec::with_capacity(size.min(len as usize));
+
+    for i in 0..len {
+        if nodes.len() >= size {
+            break;
+        }
+        let j = i + rng.below(len - i);
+        let rank = swapped.get(&j).copied().unwrap_or(j);
+        swapped.insert(j, swapped.get(&i).copied().unwrap_or(i));
+
+        let nid = neighbors
+            .select(node_id::from_u64(rank).expect("ranks in a set fit its node IDs"))
+            .expect("ranks below the length of a set are in it");
+        if keep(nid) {
+            nodes.push(nid);
+        }
+    }
+    nodes.sort_unstable();
+    nodes
+}
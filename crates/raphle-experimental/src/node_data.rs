@@ -0,0 +1,156 @@
+use hashbrown::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::node_id::{NodeId, NodeSet};
+
+/// A small typed value attached to a node.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PropertyValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl PropertyValue {
+    /// Returns the value as a number, if it is one.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            PropertyValue::Int(value) => Some(value as f64),
+            PropertyValue::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for PropertyValue {
+    fn from(raw: &str) -> Self {
+        if let Ok(value) = raw.parse::<i64>() {
+            PropertyValue::Int(value)
+        } else if let Ok(value) = raw.parse::<f64>() {
+            PropertyValue::Float(value)
+        } else if let Ok(value) = raw.parse::<bool>() {
+            PropertyValue::Bool(value)
+        } else {
+            PropertyValue::Str(raw.to_string())
+        }
+    }
+}
+
+impl rusqlite::ToSql for PropertyValue {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        match self {
+            PropertyValue::Bool(value) => value.to_sql(),
+            PropertyValue::Int(value) => value.to_sql(),
+            PropertyValue::Float(value) => value.to_sql(),
+            PropertyValue::Str(value) => value.to_sql(),
+        }
+    }
+}
+
+/// Node labels, indexed as label to [`NodeSet`] so they can be intersected with adjacency
+/// bitmaps, plus a small property map per node.
+#[derive(Default)]
+pub struct NodeData {
+    labels: HashMap<String, NodeSet>,
+    properties: HashMap<NodeId, HashMap<String, PropertyValue>>,
+
+    dirty_labels: HashSet<String>,
+    dirty_properties: NodeSet,
+}
+
+impl NodeData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_label(&mut self, nid: NodeId, label: &str) {
+        if self.labels.entry_ref(label).or_default().insert(nid) {
+            self.dirty_labels.insert(label.to_string());
+        }
+    }
+
+    pub fn remove_label(&mut self, nid: NodeId, label: &str) {
+        if let Some(members) = self.labels.get_mut(label) {
+            if members.remove(nid) {
+                self.dirty_labels.insert(label.to_string());
+            }
+        }
+    }
+
+    /// Returns the nodes carrying a label.
+    pub fn label_members(&self, label: &str) -> Option<&NodeSet> {
+        self.labels.get(label)
+    }
+
+    /// Returns the labels of a node, sorted by name.
+    pub fn labels_of(&self, nid: NodeId) -> Vec<String> {
+        let mut labels: Vec<_> = self
+            .labels
+            .iter()
+            .filter(|(_, members)| members.contains(nid))
+            .map(|(label, _)| label.clone())
+            .collect();
+        labels.sort();
+        labels
+    }
+
+    pub fn set_property(&mut self, nid: NodeId, key: &str, value: PropertyValue) {
+        self.properties
+            .entry(nid)
+            .or_default()
+            .insert(key.to_string(), value);
+        self.dirty_properties.insert(nid);
+    }
+
+    pub fn remove_property(&mut self, nid: NodeId, key: &str) {
+        if let Some(properties) = self.properties.get_mut(&nid) {
+            if properties.remove(key).is_some() {
+                self.dirty_properties.insert(nid);
+            }
+            if properties.is_empty() {
+                self.properties.remove(&nid);
+            }
+        }
+    }
+
+    /// Drops every label and property of a node, e.g. once the node is removed.
+    pub fn remove_node(&mut self, nid: NodeId) {
+        for (label, members) in self.labels.iter_mut() {
+            if members.remove(nid) {
+                self.dirty_labels.insert(label.clone());
+            }
+        }
+        if self.properties.remove(&nid).is_some() {
+            self.dirty_properties.insert(nid);
+        }
+    }
+
+    /// Iterates over every label and the nodes carrying it.
+    pub fn labels(&self) -> impl Iterator<Item = (&String, &NodeSet)> {
+        self.labels.iter()
+    }
+
+    /// Iterates over every node with properties.
+    pub fn properties(&self) -> impl Iterator<Item = (NodeId, &HashMap<String, PropertyValue>)> {
+        self.properties
+            .iter()
+            .map(|(&nid, properties)| (nid, properties))
+    }
+
+    /// Returns the properties of a node.
+    pub fn properties_of(&self, nid: NodeId) -> Option<&HashMap<String, PropertyValue>> {
+        self.properties.get(&nid)
+    }
+
+    /// Labels and nodes whose data changed since the last flush.
+    pub fn dirty(&self) -> (&HashSet<String>, &NodeSet) {
+        (&self.dirty_labels, &self.dirty_properties)
+    }
+
+    pub fn clear_dirty(&mut self) {
+        self.dirty_labels.clear();
+        self.dirty_properties.clear();
+    }
+}
@@ -0,0 +1,46 @@
+//! Node-ID width of the graph. Node-IDs are `u32`s backed by [`RoaringBitmap`]s by default;
+//! enabling the `wide-ids` feature switches them to `u64`s backed by [`RoaringTreemap`]s.
+//!
+//! [`RoaringBitmap`]: roaring::RoaringBitmap
+//! [`RoaringTreemap`]: roaring::RoaringTreemap
+
+#[cfg(not(feature = "wide-ids"))]
+pub type NodeId = u32;
+
+#[cfg(not(feature = "wide-ids"))]
+pub type NodeSet = roaring::RoaringBitmap;
+
+#[cfg(feature = "wide-ids")]
+pub type NodeId = u64;
+
+#[cfg(feature = "wide-ids")]
+pub type NodeSet = roaring::RoaringTreemap;
+
+/// Widens a node-ID to a `u64`.
+#[cfg(not(feature = "wide-ids"))]
+pub fn to_u64(nid: NodeId) -> u64 {
+    nid as u64
+}
+
+/// Widens a node-ID to a `u64`.
+#[cfg(feature = "wide-ids")]
+pub fn to_u64(nid: NodeId) -> u64 {
+    nid
+}
+
+/// Narrows a `u64` to a node-ID, if it fits.
+#[cfg(not(feature = "wide-ids"))]
+pub fn from_u64(id: u64) -> Option<NodeId> {
+    NodeId::try_from(id).ok()
+}
+
+/// Narrows a `u64` to a node-ID, if it fits.
+#[cfg(feature = "wide-ids")]
+pub fn from_u64(id: u64) -> Option<NodeId> {
+    Some(id)
+}
+
+/// SQLite integers are signed 64-bit, so node-IDs are stored bit-for-bit as `i64`s.
+pub fn to_sql(nid: NodeId) -> i64 {
+    to_u64(nid) as i64
+}
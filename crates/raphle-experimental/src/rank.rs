@@ -0,0 +1,148 @@
+use crate::node_id::NodeId;
+use crate::snapshot::Snapshot;
+
+/// Scores of the nodes of a snapshot, with the nodes ordered by score.
+pub struct Ranking {
+    nodes: Vec<NodeId>,
+    scores: Vec<f64>,
+    /// Indices of the nodes by descending score, ties broken by lower node ID.
+    order: Vec<u32>,
+    /// Position of each node in `order`.
+    positions: Vec<u32>,
+}
+
+impl Ranking {
+    /// Ranks the nodes of a snapshot by their scores, given by index.
+    pub fn new(snapshot: &Snapshot, scores: Vec<f64>) -> Self {
+        let nodes: Vec<NodeId> = (0..snapshot.len()).map(|i| snapshot.node(i)).collect();
+        let mut order: Vec<u32> = (0..nodes.len() as u32).collect();
+        order.sort_unstable_by(|&a, &b| {
+            scores[b as usize]
+                .total_cmp(&scores[a as usize])
+                .then(a.cmp(&b))
+        });
+        let mut positions = vec![0; nodes.len()];
+        for (position, &index) in order.iter().enumerate() {
+            positions[index as usize] = position as u32;
+        }
+
+        Ranking {
+            nodes,
+            scores,
+            order,
+            positions,
+        }
+    }
+
+    /// Returns up to `limit` of the best scored nodes with their scores. Nodes scoring zero are
+    /// left out.
+    pub fn top(&self, limit: usize) -> Vec<(NodeId, f64)> {
+        self.order
+            .iter()
+            .map(|&index| (self.nodes[index as usize], self.scores[index as usize]))
+            .take_while(|&(_, score)| score > 0.0)
+            .take(limit)
+            .collect()
+    }
+
+    /// Returns the score of a node and its position in the ranking, counting from zero.
+    pub fn score(&self, nid: NodeId) -> Option<(f64, usize)> {
+        let index = self.nodes.binary_search(&nid).ok()?;
+        Some((self.scores[index], self.positions[index] as usize))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PageRankParams {
+    /// Probability of following an edge rather than jumping to a random node.
+    pub damping: f64,
+    /// Iteration stops once the scores change by less than this in total.
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for PageRankParams {
+    fn default() -> Self {
+        PageRankParams {
+            damping: 0.85,
+            tolerance: 1e-6,
+            max_iterations: 100,
+        }
+    }
+}
+
+pub struct PageRank {
+    pub ranking: Ranking,
+    pub iterations: usize,
+    /// Whether the scores settled within the tolerance before the iteration limit.
+    pub converged: bool,
+}
+
+/// Computes PageRank over a snapshot by power iteration. Given `seeds`, it computes
+/// personalized PageRank instead, where random jumps only land on the seeds, so scores measure
+/// closeness to them. Seeds outside the snapshot are ignored, and without any seed in it, plain
+/// PageRank is computed. The mass of nodes without outgoing edges is spread the same way as
+/// random jumps. Scores sum to one.
+pub fn pagerank(
+    snapshot: &Snapshot,
+    params: &PageRankParams,
+    seeds: Option<&[NodeId]>,
+) -> PageRank {
+    let n = snapshot.len();
+    let mut indices: Vec<usize> = seeds
+        .into_iter()
+        .flatten()
+        .filter_map(|&nid| snapshot.index_of(nid))
+        .collect();
+    indices.sort_unstable();
+    indices.dedup();
+    let teleport = if indices.is_empty() {
+        vec![1.0 / n as f64; n]
+    } else {
+        let mut teleport = vec![0.0; n];
+        for &index in &indices {
+            teleport[index] = 1.0 / indices.len() as f64;
+        }
+        teleport
+    };
+    let out_degrees: Vec<usize> = (0..n).map(|i| snapshot.outgoing(i).len()).collect();
+
+    let mut scores = teleport.clone();
+    let mut next = vec![0.0; n];
+    let mut contributions = vec![0.0; n];
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < params.max_iterations && !converged {
+        iterations += 1;
+
+        let mut dangling = 0.0;
+        for i in 0..n {
+            if out_degrees[i] == 0 {
+                dangling += scores[i];
+                contributions[i] = 0.0;
+            } else {
+                contributions[i] = scores[i] / out_degrees[i] as f64;
+            }
+        }
+
+        let mut change = 0.0;
+        for i in 0..n {
+            let inflow: f64 = snapshot
+                .incoming(i)
+                .iter()
+                .map(|&j| contributions[j as usize])
+                .sum();
+            next[i] = params.damping * (inflow + dangling * teleport[i])
+                + (1.0 - params.damping) * teleport[i];
+            change += (next[i] - scores[i]).abs();
+        }
+        std::mem::swap(&mut scores, &mut next);
+        converged = change < params.tolerance;
+    }
+
+    PageRank {
+        ranking: Ranking::new(snapshot, scores),
+        iterations,
+        converged,
+    }
+}
@@ -0,0 +1,161 @@
+use hashbrown::HashMap;
+use serde::Deserialize;
+
+use crate::node_id::NodeId;
+use crate::relation::RelationId;
+use crate::rwlocked_graph::{Direction, RwLockedGraph};
+
+/// Scores a candidate reached from a user through the nodes the user has edges to.
+pub trait Scorer {
+    /// Weight of one path from the user to a candidate through `via`, given the out-degree of
+    /// `via`.
+    fn path_weight(&self, via_degree: u64) -> f64;
+
+    /// Final score of a candidate from the sum of its path weights, the number of those paths,
+    /// the out-degree of the user and the in-degree of the candidate.
+    fn score(&self, weight: f64, paths: u64, user_degree: u64, candidate_degree: u64) -> f64;
+}
+
+/// Counts the nodes the user has edges to that have an edge to the candidate.
+pub struct CommonNeighbors;
+
+impl Scorer for CommonNeighbors {
+    fn path_weight(&self, _via_degree: u64) -> f64 {
+        1.0
+    }
+
+    fn score(&self, weight: f64, _paths: u64, _user_degree: u64, _candidate_degree: u64) -> f64 {
+        weight
+    }
+}
+
+/// Like [`CommonNeighbors`], but paths through nodes with many edges count for less. Uses
+/// `ln(1 + degree)` so that nodes with a single edge still count.
+pub struct AdamicAdar;
+
+impl Scorer for AdamicAdar {
+    fn path_weight(&self, via_degree: u64) -> f64 {
+        1.0 / (via_degree as f64).ln_1p()
+    }
+
+    fn score(&self, weight: f64, _paths: u64, _user_degree: u64, _candidate_degree: u64) -> f64 {
+        weight
+    }
+}
+
+/// Common neighbors over the union of the user's outgoing and the candidate's incoming
+/// neighbors.
+pub struct Jaccard;
+
+impl Scorer for Jaccard {
+    fn path_weight(&self, _via_degree: u64) -> f64 {
+        1.0
+    }
+
+    fn score(&self, _weight: f64, paths: u64, user_degree: u64, candidate_degree: u64) -> f64 {
+        let union = (user_degree + candidate_degree).saturating_sub(paths);
+        if union == 0 {
+            0.0
+        } else {
+            paths as f64 / union as f64
+        }
+    }
+}
+
+/// The built-in scorers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScorerKind {
+    #[default]
+    CommonNeighbors,
+    AdamicAdar,
+    Jaccard,
+}
+
+impl ScorerKind {
+    pub fn scorer(self) -> &'static dyn Scorer {
+        match self {
+            ScorerKind::CommonNeighbors => &CommonNeighbors,
+            ScorerKind::AdamicAdar => &AdamicAdar,
+            ScorerKind::Jaccard => &Jaccard,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Recommendation {
+    pub node: NodeId,
+    pub score: f64,
+}
+
+pub struct Recommendations {
+    /// Best candidates first, ties broken by lower node ID.
+    pub candidates: Vec<Recommendation>,
+    /// Whether part of the neighborhood was skipped for exceeding the fan-out.
+    pub truncated: bool,
+}
+
+/// Recommends nodes for `node` to connect to from its friends of friends: the nodes reached in
+/// two outgoing hops, scored by `scorer`. Nodes it already has an edge to, itself and nodes
+/// sharing an edge of `block_relation` with it either way are left out.
+///
+/// At most `max_fanout` of its neighbors are walked, and neighbors with more than `max_fanout`
+/// outgoing edges are skipped, which keeps accounts following everyone from blowing up the
+/// cost. The adjacency is read under a single lock.
+pub fn recommend(
+    graph: &RwLockedGraph,
+    node: NodeId,
+    relation: RelationId,
+    block_relation: Option<RelationId>,
+    scorer: &dyn Scorer,
+    limit: usize,
+    max_fanout: u64,
+) -> Recommendations {
+    let adjacency = graph.read_adjacency();
+    let following = adjacency.neighbors(node, relation, Direction::Outgoing);
+
+    let mut excluded = following.clone();
+    excluded.insert(node);
+    if let Some(block_relation) = block_relation {
+        excluded |= adjacency.neighbors(node, block_relation, Direction::Outgoing);
+        excluded |= adjacency.neighbors(node, block_relation, Direction::Incoming);
+    }
+
+    let mut truncated = following.len() > max_fanout;
+    let mut paths: HashMap<NodeId, (f64, u64)> = HashMap::new();
+    for via in following.iter().take(max_fanout as usize) {
+        let mut candidates = adjacency.neighbors(via, relation, Direction::Outgoing);
+        let via_degree = candidates.len();
+        if via_degree > max_fanout {
+            truncated = true;
+            continue;
+        }
+
+        candidates -= &excluded;
+        let weight = scorer.path_weight(via_degree);
+        for candidate in candidates.iter() {
+            let entry = paths.entry(candidate).or_insert((0.0, 0));
+            entry.0 += weight;
+            entry.1 += 1;
+        }
+    }
+
+    let user_degree = following.len();
+    let mut candidates: Vec<Recommendation> = paths
+        .into_iter()
+        .map(|(candidate, (weight, count))| {
+            let candidate_degree = adjacency.degree(candidate, relation, Direction::Incoming);
+            Recommendation {
+                node: candidate,
+                score: scorer.score(weight, count, user_degree, candidate_degree),
+            }
+        })
+        .collect();
+    candidates.sort_unstable_by(|a, b| b.score.total_cmp(&a.score).then(a.node.cmp(&b.node)));
+    candidates.truncate(limit);
+
+    Recommendations {
+        candidates,
+        truncated,
+    }
+}
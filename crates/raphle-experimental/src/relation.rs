@@ -0,0 +1,61 @@
+use hashbrown::HashMap;
+
+/// Compact ID of a named relation (follows, blocks, ...), used to key per-relation bitmaps.
+pub type RelationId = u16;
+
+/// Relation used when a request or input file does not name one.
+pub const DEFAULT_RELATION: &str = "default";
+
+/// Interns relation names so every node stores its bitmaps under a small [`RelationId`].
+#[derive(Clone)]
+pub struct RelationRegistry {
+    ids: HashMap<String, RelationId>,
+    names: Vec<String>,
+}
+
+impl RelationRegistry {
+    pub fn new() -> Self {
+        let mut registry = RelationRegistry {
+            ids: HashMap::new(),
+            names: Vec::new(),
+        };
+        registry.get_or_insert(DEFAULT_RELATION);
+        registry
+    }
+
+    /// Returns the ID of a relation, if it has been seen before.
+    pub fn get(&self, name: &str) -> Option<RelationId> {
+        self.ids.get(name).copied()
+    }
+
+    /// Returns the ID of a relation, registering it if it is new.
+    pub fn get_or_insert(&mut self, name: &str) -> RelationId {
+        if let Some(&rid) = self.ids.get(name) {
+            return rid;
+        }
+
+        let rid = RelationId::try_from(self.names.len()).expect("too many relations");
+        self.ids.insert(name.to_string(), rid);
+        self.names.push(name.to_string());
+        rid
+    }
+
+    /// Returns the name of a relation.
+    pub fn name(&self, rid: RelationId) -> Option<&str> {
+        self.names.get(rid as usize).map(String::as_str)
+    }
+
+    /// Iterates over all registered relations.
+    pub fn iter(&self) -> impl Iterator<Item = (RelationId, &str)> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(rid, name)| (rid as RelationId, name.as_str()))
+    }
+}
+
+impl Default for RelationRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,34 @@
+/// A small seeded pseudo-random number generator (SplitMix64), so that randomized analytics
+/// give the same results for the same seed. Not suitable for anything security related.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number below `bound`, which must not be zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        // multiply-shift keeps the bias negligible for bounds far below 2^64
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+
+    /// Shuffles a slice in place with Fisher-Yates.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
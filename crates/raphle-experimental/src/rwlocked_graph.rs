@@ -1,11 +1,38 @@
-use std::{fs::File, io::BufReader, sync::RwLock};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::BufReader,
+    ops::RangeInclusive,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock, RwLockReadGuard,
+    },
+};
 use hashbrown::HashMap;
-use roaring::bitmap::RoaringBitmap;
 use csv::ReaderBuilder;
 use tracing::{info, error};
 use rusqlite::{Connection, Result};
 
-enum GraphAction {
+use crate::batch::{Batch, BatchOp, FailedPrecondition, Precondition};
+use crate::change_log::{self, ChangeLog, ChangeLogEntry};
+use crate::degree_index::DegreeIndex;
+use crate::edge_properties::{EdgeKey, EdgePropertyStore};
+use crate::expiry::ExpiryIndex;
+use crate::id_map::{IdMap, NodeKey};
+use crate::neighbor_list::{self, NeighborFilter, NeighborList, Selection};
+use crate::node_data::{NodeData, PropertyValue};
+use crate::node_id::{self, NodeId, NodeSet};
+use crate::relation::{RelationId, RelationRegistry, DEFAULT_RELATION};
+
+/// Name of the edge property holding an edge's unix timestamp, in seconds. A loader column of
+/// this name sets the timestamps of the loaded edges.
+pub const TIMESTAMP_PROPERTY: &str = "timestamp";
+
+/// Database file graphs are flushed to unless given their own.
+pub const DEFAULT_DB_PATH: &str = "data/raphle.db";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphAction {
     AddEdge,
     RemoveEdge,
     // AddNode,
@@ -14,22 +41,230 @@ enum GraphAction {
 
 struct QueueGraphActionItem {
     action: GraphAction,
-    source: u32,
-    target: u32,  // How could we cascade targets? 
+    source: NodeId,
+    target: NodeId,  // How could we cascade targets? 
+    relation: RelationId,
 }
 
+/// Per-relation adjacency of a single node.
 pub struct RwLockedNodeMap {
-    outgoing_edges: RwLock<RoaringBitmap>,
-    incoming_edges: RwLock<RoaringBitmap>,
+    outgoing_edges: RwLock<HashMap<RelationId, NodeSet>>,
+    incoming_edges: RwLock<HashMap<RelationId, NodeSet>>,
+}
+
+impl RwLockedNodeMap {
+    fn new() -> Self {
+        RwLockedNodeMap {
+            outgoing_edges: RwLock::new(HashMap::new()),
+            incoming_edges: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl Clone for RwLockedNodeMap {
+    fn clone(&self) -> Self {
+        RwLockedNodeMap {
+            outgoing_edges: RwLock::new(self.outgoing_edges.read().unwrap().clone()),
+            incoming_edges: RwLock::new(self.incoming_edges.read().unwrap().clone()),
+        }
+    }
+}
+
+/// Which side of a node's adjacency to read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Outgoing,
+    Incoming,
+}
+
+type NodeMaps = HashMap<NodeId, RwLockedNodeMap>;
+
+/// Inserts an edge into the adjacency, returning whether it already existed.
+fn insert_edge(nodes: &mut NodeMaps, source: NodeId, target: NodeId, relation: RelationId) -> bool {
+    let source_map = nodes.entry(source).or_insert_with(RwLockedNodeMap::new);
+    let inserted = source_map
+        .outgoing_edges
+        .write()
+        .unwrap()
+        .entry(relation)
+        .or_default()
+        .insert(target);
+
+    let target_map = nodes.entry(target).or_insert_with(RwLockedNodeMap::new);
+    target_map
+        .incoming_edges
+        .write()
+        .unwrap()
+        .entry(relation)
+        .or_default()
+        .insert(source);
+
+    !inserted
+}
+
+/// Deletes an edge from the adjacency, returning whether it existed.
+fn delete_edge(nodes: &mut NodeMaps, source: NodeId, target: NodeId, relation: RelationId) -> bool {
+    let mut removed = false;
+    if let Some(source_map) = nodes.get_mut(&source) {
+        if let Some(outgoing) = source_map.outgoing_edges.write().unwrap().get_mut(&relation) {
+            removed = outgoing.remove(target);
+        }
+    }
+
+    if let Some(target_map) = nodes.get_mut(&target) {
+        if let Some(incoming) = target_map.incoming_edges.write().unwrap().get_mut(&relation) {
+            incoming.remove(source);
+        }
+    }
+    removed
+}
+
+/// Links two nodes of an undirected graph by adding each to the other's outgoing edges,
+/// returning whether they were already linked. Incoming edges stay empty.
+fn link_nodes(nodes: &mut NodeMaps, a: NodeId, b: NodeId, relation: RelationId) -> bool {
+    let mut inserted = false;
+    for (node, neighbor) in [(a, b), (b, a)] {
+        let node_map = nodes.entry(node).or_insert_with(RwLockedNodeMap::new);
+        inserted |= node_map
+            .outgoing_edges
+            .write()
+            .unwrap()
+            .entry(relation)
+            .or_default()
+            .insert(neighbor);
+    }
+    !inserted
+}
+
+/// Unlinks two nodes of an undirected graph, returning whether they were linked.
+fn unlink_nodes(nodes: &mut NodeMaps, a: NodeId, b: NodeId, relation: RelationId) -> bool {
+    let mut linked = false;
+    for (node, neighbor) in [(a, b), (b, a)] {
+        if let Some(node_map) = nodes.get_mut(&node) {
+            if let Some(outgoing) = node_map.outgoing_edges.write().unwrap().get_mut(&relation) {
+                linked |= outgoing.remove(neighbor);
+            }
+        }
+    }
+    linked
+}
+
+/// Checks if the adjacency contains an edge, whether or not it has expired.
+fn contains_edge(nodes: &NodeMaps, source: NodeId, target: NodeId, relation: RelationId) -> bool {
+    nodes.get(&source).is_some_and(|node| {
+        node.outgoing_edges
+            .read()
+            .unwrap()
+            .get(&relation)
+            .is_some_and(|outgoing| outgoing.contains(target))
+    })
+}
+
+/// Returns the number of edges of a relation stored for a node in a direction, whether or not
+/// they have expired.
+fn stored_degree(nodes: &NodeMaps, node: NodeId, relation: RelationId, direction: Direction) -> u64 {
+    nodes.get(&node).map_or(0, |node_map| {
+        let edges = match direction {
+            Direction::Outgoing => node_map.outgoing_edges.read().unwrap(),
+            Direction::Incoming => node_map.incoming_edges.read().unwrap(),
+        };
+        edges.get(&relation).map_or(0, |neighbors| neighbors.len())
+    })
+}
+
+/// Turns optional bounds on a unix time, in seconds, into a window of edge timestamps.
+fn timestamp_window(since: Option<u64>, until: Option<u64>) -> RangeInclusive<i64> {
+    let since = since.map_or(i64::MIN, |since| since.try_into().unwrap_or(i64::MAX));
+    let until = until.map_or(i64::MAX, |until| until.try_into().unwrap_or(i64::MAX));
+    since..=until
+}
+
+/// Indexes the degree of every node of an adjacency.
+fn index_degrees(nodes: &NodeMaps) -> DegreeIndex {
+    let mut degrees = DegreeIndex::new();
+    for (&nid, node_map) in nodes.iter() {
+        for (direction, edges) in [
+            (Direction::Outgoing, node_map.outgoing_edges.read().unwrap()),
+            (Direction::Incoming, node_map.incoming_edges.read().unwrap()),
+        ] {
+            for (&relation, neighbors) in edges.iter() {
+                degrees.update(nid, relation, direction, 0, neighbors.len());
+            }
+        }
+    }
+    degrees
+}
+
+/// A read-locked view of the adjacency of a graph, so that several neighbor sets can be read
+/// consistently. Writers are blocked for as long as the view is held.
+pub struct AdjacencyView<'a> {
+    graph: &'a RwLockedGraph,
+    nodes: RwLockReadGuard<'a, NodeMaps>,
+    edge_expiries: RwLockReadGuard<'a, ExpiryIndex>,
+    now: u64,
+}
+
+impl AdjacencyView<'_> {
+    /// Returns every node of the graph, in no particular order.
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes.keys().copied()
+    }
+
+    /// Returns the neighbors of a node along a relation, leaving out expired edges. Undirected
+    /// graphs are read along their edges, whatever the direction.
+    pub fn neighbors(&self, node: NodeId, relation: RelationId, direction: Direction) -> NodeSet {
+        let direction = if self.graph.directed { direction } else { Direction::Outgoing };
+        let Some(node_map) = self.nodes.get(&node) else {
+            return NodeSet::new();
+        };
+        let edges = match direction {
+            Direction::Outgoing => node_map.outgoing_edges.read().unwrap(),
+            Direction::Incoming => node_map.incoming_edges.read().unwrap(),
+        };
+        let mut neighbors = edges.get(&relation).cloned().unwrap_or_default();
+        if !self.edge_expiries.is_empty() {
+            for neighbor in self.graph.expired_neighbors(&self.edge_expiries, node, relation, direction, self.now) {
+                neighbors.remove(neighbor);
+            }
+        }
+        neighbors
+    }
+
+    /// Returns the number of neighbors of a node along a relation, without copying them.
+    pub fn degree(&self, node: NodeId, relation: RelationId, direction: Direction) -> u64 {
+        let direction = if self.graph.directed { direction } else { Direction::Outgoing };
+        let Some(node_map) = self.nodes.get(&node) else {
+            return 0;
+        };
+        let edges = match direction {
+            Direction::Outgoing => node_map.outgoing_edges.read().unwrap(),
+            Direction::Incoming => node_map.incoming_edges.read().unwrap(),
+        };
+        let degree = edges.get(&relation).map_or(0, |neighbors| neighbors.len());
+        if self.edge_expiries.is_empty() {
+            return degree;
+        }
+        let expired = self.graph.expired_neighbors(&self.edge_expiries, node, relation, direction, self.now);
+        degree.saturating_sub(expired.len() as u64)
+    }
 }
 
 pub struct RwLockedGraph {
-    nodes: RwLock<HashMap<u32, RwLockedNodeMap>>,
+    nodes: RwLock<NodeMaps>,
     pending_action_queue: RwLock<Vec<QueueGraphActionItem>>,
     pub is_loaded: RwLock<bool>,
 
-    updated_nodes: RwLock<RoaringBitmap> // Think we should track all state changes for graph
-                                         // playback
+    change_log: RwLock<ChangeLog>, // Tracks all state changes for flushing and graph playback
+
+    relations: RwLock<RelationRegistry>,
+    node_data: RwLock<NodeData>,
+    edge_properties: RwLock<EdgePropertyStore>,
+    edge_expiries: RwLock<ExpiryIndex>,
+    degrees: RwLock<DegreeIndex>,
+    id_map: Option<RwLock<IdMap>>, // Only set when nodes are addressed by external keys
+    directed: bool,
+    db_path: String,
+    load_progress: Arc<AtomicUsize>, // Rows read by the running or last load
 }
 
 impl RwLockedGraph {
@@ -38,23 +273,139 @@ impl RwLockedGraph {
             nodes: RwLock::new(HashMap::with_capacity(expected_node_count as usize)),
             pending_action_queue: RwLock::new(Vec::new()),
             is_loaded: RwLock::new(false),
-            updated_nodes: RwLock::new(RoaringBitmap::new()),
+            change_log: RwLock::new(ChangeLog::new()),
+            relations: RwLock::new(RelationRegistry::new()),
+            node_data: RwLock::new(NodeData::new()),
+            edge_properties: RwLock::new(EdgePropertyStore::new()),
+            edge_expiries: RwLock::new(ExpiryIndex::new()),
+            degrees: RwLock::new(DegreeIndex::new()),
+            id_map: None,
+            directed: true,
+            db_path: DEFAULT_DB_PATH.to_string(),
+            load_progress: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Creates a graph whose nodes are addressed by external [`NodeKey`]s, which are mapped to
+    /// dense internal IDs as they are first seen.
+    pub fn with_id_mapping(expected_node_count: u32) -> Self {
+        RwLockedGraph {
+            id_map: Some(RwLock::new(IdMap::new(expected_node_count))),
+            ..RwLockedGraph::new(expected_node_count)
+        }
+    }
+
+    /// Makes the graph undirected. Adding or removing an edge between two nodes works the same in
+    /// either direction, and every edge is listed among the outgoing edges of both of its nodes.
+    pub fn undirected(self) -> Self {
+        RwLockedGraph {
+            directed: false,
+            ..self
+        }
+    }
+
+    /// Persists the graph to its own database file instead of [`DEFAULT_DB_PATH`].
+    pub fn with_db_path(self, db_path: &str) -> Self {
+        RwLockedGraph {
+            db_path: db_path.to_string(),
+            ..self
+        }
+    }
+
+    /// Keeps flushed changes in the change log for `retention` seconds instead of
+    /// [`change_log::DEFAULT_RETENTION_SECS`], which bounds how far back the graph can be replayed.
+    pub fn with_change_retention(self, retention: u64) -> Self {
+        RwLockedGraph {
+            change_log: RwLock::new(ChangeLog::with_retention(retention)),
+            ..self
+        }
+    }
+
+    /// Creates an empty graph with the same settings and database file, e.g. to load a new
+    /// version of the graph in the background before swapping it in. Known node keys and
+    /// relations keep their IDs.
+    pub fn empty_like(&self) -> Self {
+        let expected_node_count = u32::try_from(self.nodes.read().unwrap().len()).unwrap_or(u32::MAX);
+        RwLockedGraph {
+            relations: RwLock::new(self.relations.read().unwrap().clone()),
+            id_map: self
+                .id_map
+                .as_ref()
+                .map(|id_map| RwLock::new(id_map.read().unwrap().clone())),
+            directed: self.directed,
+            db_path: self.db_path.clone(),
+            change_log: RwLock::new(ChangeLog::with_retention(self.change_log.read().unwrap().retention())),
+            ..RwLockedGraph::new(expected_node_count)
+        }
+    }
+
+    /// Returns whether edges have a direction.
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// Orders the nodes of an undirected edge so both directions share one key for properties,
+    /// expiry and the change log.
+    fn edge_key(&self, source: NodeId, target: NodeId, relation: RelationId) -> EdgeKey {
+        if !self.directed && target < source {
+            (target, source, relation)
+        } else {
+            (source, target, relation)
         }
     }
 
-    pub fn enqueue_add_edge(&self, source: u32, target: u32) {
+    /// Inserts an edge into an adjacency of this graph, returning whether it already existed.
+    fn insert_into(&self, nodes: &mut NodeMaps, source: NodeId, target: NodeId, relation: RelationId) -> bool {
+        if self.directed {
+            insert_edge(nodes, source, target, relation)
+        } else {
+            link_nodes(nodes, source, target, relation)
+        }
+    }
+
+    /// Deletes an edge from an adjacency of this graph, returning whether it existed.
+    fn delete_from(&self, nodes: &mut NodeMaps, source: NodeId, target: NodeId, relation: RelationId) -> bool {
+        if self.directed {
+            delete_edge(nodes, source, target, relation)
+        } else {
+            unlink_nodes(nodes, source, target, relation)
+        }
+    }
+
+    /// Moves the nodes of an edge that was just added to or removed from the adjacency to their
+    /// new degrees in the degree index.
+    fn reindex_degrees(&self, nodes: &NodeMaps, source: NodeId, target: NodeId, relation: RelationId, added: bool) {
+        let ends = if self.directed {
+            vec![(source, Direction::Outgoing), (target, Direction::Incoming)]
+        } else if source == target {
+            vec![(source, Direction::Outgoing)]
+        } else {
+            vec![(source, Direction::Outgoing), (target, Direction::Outgoing)]
+        };
+
+        let mut degrees = self.degrees.write().unwrap();
+        for (node, direction) in ends {
+            let degree = stored_degree(nodes, node, relation, direction);
+            let previous = if added { degree - 1 } else { degree + 1 };
+            degrees.update(node, relation, direction, previous, degree);
+        }
+    }
+
+    pub fn enqueue_add_edge(&self, source: NodeId, target: NodeId, relation: RelationId) {
         self.pending_action_queue.write().unwrap().push(QueueGraphActionItem {
             action: GraphAction::AddEdge,
             source,
             target,
+            relation,
         });
     }
 
-    pub fn enqueue_remove_edge(&self, source: u32, target: u32) {
+    pub fn enqueue_remove_edge(&self, source: NodeId, target: NodeId, relation: RelationId) {
         self.pending_action_queue.write().unwrap().push(QueueGraphActionItem {
             action: GraphAction::RemoveEdge,
             source,
             target,
+            relation,
         });
     }
 
@@ -62,84 +413,727 @@ impl RwLockedGraph {
         self.pending_action_queue.read().unwrap().len()
     }
 
-    /// Adds an edge between a given source and target node.
-    pub fn add_edge(&self, source: u32, target: u32) {
-        let mut nodes = self.nodes.write().unwrap();
-        let source_map = nodes.entry(source).or_insert_with(|| RwLockedNodeMap {
-            outgoing_edges: RwLock::new(RoaringBitmap::new()),
-            incoming_edges: RwLock::new(RoaringBitmap::new()),
-        });
-        source_map.outgoing_edges.write().unwrap().insert(target);
+    /// Adds an edge of a relation between a given source and target node.
+    pub fn add_edge(&self, source: NodeId, target: NodeId, relation: RelationId) {
+        self.add_edge_locked(&mut self.nodes.write().unwrap(), source, target, relation);
+    }
 
-        let target_map = nodes.entry(target).or_insert_with(|| RwLockedNodeMap {
-            outgoing_edges: RwLock::new(RoaringBitmap::new()),
-            incoming_edges: RwLock::new(RoaringBitmap::new()),
-        });
-        target_map.incoming_edges.write().unwrap().insert(source);
+    fn add_edge_locked(&self, nodes: &mut NodeMaps, source: NodeId, target: NodeId, relation: RelationId) {
+        let existed = self.insert_into(nodes, source, target, relation);
+        if !existed {
+            self.reindex_degrees(nodes, source, target, relation, true);
+        }
+        let (source, target, relation) = self.edge_key(source, target, relation);
+        // Re-adding an edge makes it permanent unless a new expiry is set
+        self.edge_expiries.write().unwrap().clear(&(source, target, relation));
+
+        // Log the change so we can update on-disk version and play it back
+        self.change_log
+            .write()
+            .unwrap()
+            .record(GraphAction::AddEdge, source, target, relation, existed);
+    }
+
+    /// Removes the edge of a relation between a given source and target node.
+    pub fn remove_edge(&self, source: NodeId, target: NodeId, relation: RelationId) {
+        self.remove_edge_locked(&mut self.nodes.write().unwrap(), source, target, relation);
+    }
+
+    fn remove_edge_locked(&self, nodes: &mut NodeMaps, source: NodeId, target: NodeId, relation: RelationId) {
+        let existed = self.delete_from(nodes, source, target, relation);
+        if existed {
+            self.reindex_degrees(nodes, source, target, relation, false);
+        }
+        let (source, target, relation) = self.edge_key(source, target, relation);
+        self.edge_properties.write().unwrap().remove_all(&(source, target, relation));
+        self.edge_expiries.write().unwrap().clear(&(source, target, relation));
+
+        // Log the change so we can update on-disk version and play it back
+        self.change_log
+            .write()
+            .unwrap()
+            .record(GraphAction::RemoveEdge, source, target, relation, existed);
+    }
 
-        // Add changes to updated_nodes so we can update on-disk version
-        self.updated_nodes.write().unwrap().insert(source);
-        self.updated_nodes.write().unwrap().insert(target);
+    /// Removes a node with every edge it has in any relation, along with its labels and
+    /// properties. Each removed edge is logged on its own so the removal can be played back.
+    pub fn remove_node(&self, nid: NodeId) {
+        self.remove_node_locked(&mut self.nodes.write().unwrap(), nid);
     }
 
-    /// Removes the edge between a given source and target node.
-    pub fn remove_edge(&self, source: u32, target: u32) {
+    fn remove_node_locked(&self, nodes: &mut NodeMaps, nid: NodeId) {
+        let Some(node) = nodes.get(&nid) else {
+            return;
+        };
+
+        let mut edges = Vec::new();
+        for (&relation, targets) in node.outgoing_edges.read().unwrap().iter() {
+            edges.extend(targets.iter().map(|target| (nid, target, relation)));
+        }
+        for (&relation, sources) in node.incoming_edges.read().unwrap().iter() {
+            // self-loops were already collected as outgoing edges
+            edges.extend(sources.iter().filter(|&source| source != nid).map(|source| (source, nid, relation)));
+        }
+
+        for (source, target, relation) in edges {
+            self.remove_edge_locked(nodes, source, target, relation);
+        }
+        nodes.remove(&nid);
+        self.node_data.write().unwrap().remove_node(nid);
+    }
+
+    /// Applies a batch of operations atomically. The adjacency stays write-locked while the
+    /// preconditions are checked and the operations applied, so no reader sees part of a batch.
+    /// Nothing is applied when a precondition does not hold.
+    pub fn apply_batch(&self, batch: &Batch) -> Result<(), FailedPrecondition> {
         let mut nodes = self.nodes.write().unwrap();
-        if let Some(source_map) = nodes.get_mut(&source) {
-            source_map.outgoing_edges.write().unwrap().remove(target);
+        self.check_preconditions(&nodes, &batch.preconditions)?;
+        self.apply_ops_locked(&mut nodes, &batch.ops);
+        Ok(())
+    }
+
+    /// Like [`RwLockedGraph::apply_batch`], but builds the operations only once the
+    /// preconditions hold, under the same lock. Operations that intern new keys or relations
+    /// thus register nothing when a precondition does not hold.
+    pub fn apply_batch_with(
+        &self,
+        preconditions: &[Precondition],
+        build_ops: impl FnOnce() -> Vec<BatchOp>,
+    ) -> Result<(), FailedPrecondition> {
+        let mut nodes = self.nodes.write().unwrap();
+        self.check_preconditions(&nodes, preconditions)?;
+        self.apply_ops_locked(&mut nodes, &build_ops());
+        Ok(())
+    }
+
+    fn check_preconditions(&self, nodes: &NodeMaps, preconditions: &[Precondition]) -> Result<(), FailedPrecondition> {
+        let now = change_log::now();
+        match preconditions.iter().position(|precondition| !self.precondition_holds(nodes, precondition, now)) {
+            Some(index) => Err(FailedPrecondition { index }),
+            None => Ok(()),
         }
+    }
 
-        if let Some(target_map) = nodes.get_mut(&target) {
-            target_map.incoming_edges.write().unwrap().remove(source);
+    fn apply_ops_locked(&self, nodes: &mut NodeMaps, ops: &[BatchOp]) {
+        for op in ops {
+            match op {
+                BatchOp::AddEdge { source, target, relation, properties, expires_at } => {
+                    self.add_edge_locked(nodes, *source, *target, *relation);
+                    for (key, value) in properties {
+                        self.set_edge_property(*source, *target, *relation, key, value.clone());
+                    }
+                    if let Some(expires_at) = expires_at {
+                        self.set_edge_expiry(*source, *target, *relation, *expires_at);
+                    }
+                }
+                BatchOp::RemoveEdge { source, target, relation } => {
+                    self.remove_edge_locked(nodes, *source, *target, *relation);
+                }
+                BatchOp::RemoveNode { node } => self.remove_node_locked(nodes, *node),
+            }
         }
+    }
 
-        // Add changes to updated_nodes so we can update on-disk version
-        self.updated_nodes.write().unwrap().insert(source);
-        self.updated_nodes.write().unwrap().insert(target);
+    /// Checks a precondition against the locked adjacency. Expired edges count as absent.
+    fn precondition_holds(&self, nodes: &NodeMaps, precondition: &Precondition, now: u64) -> bool {
+        let edge_expiries = self.edge_expiries.read().unwrap();
+        let is_live = |source, target, relation| {
+            contains_edge(nodes, source, target, relation)
+                && !edge_expiries.is_expired(&self.edge_key(source, target, relation), now)
+        };
+
+        match *precondition {
+            Precondition::EdgeAbsent { source, target, relation } => !is_live(source, target, relation),
+            Precondition::EdgePresent { source, target, relation } => is_live(source, target, relation),
+            Precondition::OutDegreeBelow { node, relation, limit } => {
+                let degree = nodes.get(&node).map_or(0, |node| {
+                    node.outgoing_edges.read().unwrap().get(&relation).map_or(0, |outgoing| outgoing.len())
+                });
+                let expired = self.expired_neighbors(&edge_expiries, node, relation, Direction::Outgoing, now).len();
+                degree.saturating_sub(expired as u64) < limit
+            }
+        }
     }
 
-    /// Returns the incoming_edges for a target node.
-    pub fn get_incoming_edges(&self, target: u32) -> RoaringBitmap {
-        if let Some(node) = self.nodes.read().unwrap().get(&target) {
-            node.incoming_edges.read().unwrap().clone()
-        } else {
-            RoaringBitmap::new()
+    /// Adds an edge from a bulk load. Loaded edges form the base of the change log, so only
+    /// their nodes are tracked for flushing.
+    /// Returns whether the edge was already loaded, e.g. as `(b, a)` in an undirected graph.
+    fn load_edge(&self, source: NodeId, target: NodeId, relation: RelationId) -> bool {
+        let mut nodes = self.nodes.write().unwrap();
+        let existed = self.insert_into(&mut nodes, source, target, relation);
+        if !existed {
+            self.reindex_degrees(&nodes, source, target, relation, true);
         }
+        drop(nodes);
+
+        let mut change_log = self.change_log.write().unwrap();
+        change_log.mark_base(source);
+        change_log.mark_base(target);
+        existed
+    }
+
+    /// Returns the incoming_edges of a relation for a target node.
+    pub fn get_incoming_edges(&self, target: NodeId, relation: RelationId) -> NodeSet {
+        let mut incoming = if let Some(node) = self.nodes.read().unwrap().get(&target) {
+            node.incoming_edges.read().unwrap().get(&relation).cloned().unwrap_or_default()
+        } else {
+            NodeSet::new()
+        };
+        self.remove_expired(target, &mut incoming, relation, Direction::Incoming);
+        incoming
     }
 
-    /// Returns the outgoing_edges for a source node.
-    pub fn get_outgoing_edges(&self, source: u32) -> RoaringBitmap {
-        if let Some(node) = self.nodes.read().unwrap().get(&source) {
-            node.outgoing_edges.read().unwrap().clone()
+    /// Returns the outgoing_edges of a relation for a source node.
+    pub fn get_outgoing_edges(&self, source: NodeId, relation: RelationId) -> NodeSet {
+        let mut outgoing = if let Some(node) = self.nodes.read().unwrap().get(&source) {
+            node.outgoing_edges.read().unwrap().get(&relation).cloned().unwrap_or_default()
         } else {
-            RoaringBitmap::new()
+            NodeSet::new()
+        };
+        self.remove_expired(source, &mut outgoing, relation, Direction::Outgoing);
+        outgoing
+    }
+
+    /// Returns the nodes sharing an edge of a relation with a node, in either direction. This is
+    /// the way to read an undirected graph, whose edges are all outgoing.
+    pub fn get_neighbors(&self, node: NodeId, relation: RelationId) -> NodeSet {
+        let mut neighbors = self.get_outgoing_edges(node, relation);
+        neighbors |= self.get_incoming_edges(node, relation);
+        neighbors
+    }
+
+    /// Returns the union of the neighbors of every node in a set, reading the adjacency under a
+    /// single lock. Edges that expired but have not been reaped yet are left out.
+    pub fn union_neighbors(&self, nodes: &NodeSet, relation: RelationId, direction: Direction) -> NodeSet {
+        let node_maps = self.nodes.read().unwrap();
+        let edge_expiries = self.edge_expiries.read().unwrap();
+        let now = change_log::now();
+
+        let mut union = NodeSet::new();
+        for nid in nodes.iter() {
+            let Some(node) = node_maps.get(&nid) else {
+                continue;
+            };
+            let edges = match direction {
+                Direction::Outgoing => node.outgoing_edges.read().unwrap(),
+                Direction::Incoming => node.incoming_edges.read().unwrap(),
+            };
+            let Some(neighbors) = edges.get(&relation) else {
+                continue;
+            };
+
+            let expired = if edge_expiries.is_empty() {
+                Vec::new()
+            } else {
+                self.expired_neighbors(&edge_expiries, nid, relation, direction, now)
+            };
+            if expired.is_empty() {
+                union |= neighbors;
+            } else {
+                let mut neighbors = neighbors.clone();
+                for neighbor in expired {
+                    neighbors.remove(neighbor);
+                }
+                union |= neighbors;
+            }
+        }
+        union
+    }
+
+    /// Read-locks the adjacency of the graph until the returned view is dropped.
+    pub fn read_adjacency(&self) -> AdjacencyView<'_> {
+        AdjacencyView {
+            graph: self,
+            nodes: self.nodes.read().unwrap(),
+            edge_expiries: self.edge_expiries.read().unwrap(),
+            now: change_log::now(),
+        }
+    }
+
+    /// Hides the neighbors whose edges expired but have not been reaped yet.
+    fn remove_expired(&self, node: NodeId, neighbors: &mut NodeSet, relation: RelationId, direction: Direction) {
+        let edge_expiries = self.edge_expiries.read().unwrap();
+        if edge_expiries.is_empty() {
+            return;
+        }
+        for neighbor in self.expired_neighbors(&edge_expiries, node, relation, direction, change_log::now()) {
+            neighbors.remove(neighbor);
         }
     }
 
-    /// Checks if the outgoing_edges of a source node contain a target node.
-    pub fn has_edge(&self, source: u32, target: u32) -> bool {
-        self.get_outgoing_edges(source).contains(target)
+    /// Returns the neighbors of a node whose edges expired by `now`. The expiry of an undirected
+    /// edge is kept under its ordered key, so both sides of the index are checked.
+    fn expired_neighbors(
+        &self,
+        edge_expiries: &ExpiryIndex,
+        node: NodeId,
+        relation: RelationId,
+        direction: Direction,
+        now: u64,
+    ) -> Vec<NodeId> {
+        let mut expired = edge_expiries.expired_neighbors(node, relation, direction, now);
+        if !self.directed {
+            expired.extend(edge_expiries.expired_neighbors(node, relation, Direction::Incoming, now));
+        }
+        expired
+    }
+
+    /// Sets the unix time, in seconds, at which an edge expires. Expired edges are hidden from
+    /// reads right away and removed by [`RwLockedGraph::reap_expired`].
+    pub fn set_edge_expiry(&self, source: NodeId, target: NodeId, relation: RelationId, expires_at: u64) {
+        self.edge_expiries
+            .write()
+            .unwrap()
+            .set(self.edge_key(source, target, relation), expires_at);
+    }
+
+    /// Removes every edge that expired by `now` through [`RwLockedGraph::remove_edge`], so the
+    /// removals are logged and flushed like any other. Returns the number of reaped edges.
+    pub fn reap_expired(&self, now: u64) -> usize {
+        let expired = self.edge_expiries.read().unwrap().expired(now);
+        for &(source, target, relation) in &expired {
+            self.remove_edge(source, target, relation);
+        }
+        expired.len()
+    }
+
+    /// Checks if the outgoing_edges of a relation for a source node contain a target node.
+    pub fn has_edge(&self, source: NodeId, target: NodeId, relation: RelationId) -> bool {
+        if self
+            .edge_expiries
+            .read()
+            .unwrap()
+            .is_expired(&self.edge_key(source, target, relation), change_log::now())
+        {
+            return false;
+        }
+
+        contains_edge(&self.nodes.read().unwrap(), source, target, relation)
+    }
+
+    /// Returns the number of edges of a relation a node has in a direction, from the cardinality
+    /// of its neighbor set. Undirected graphs count their edges whatever the direction.
+    pub fn degree(&self, node: NodeId, relation: RelationId, direction: Direction) -> u64 {
+        self.read_adjacency().degree(node, relation, direction)
+    }
+
+    /// Returns up to `limit` nodes with the most edges of a relation in a direction, with their
+    /// degrees, highest first. Read from the degree index, where edges that expired count until
+    /// they are reaped.
+    pub fn top_by_degree(&self, relation: RelationId, direction: Direction, limit: usize) -> Vec<(NodeId, u64)> {
+        let direction = if self.directed { direction } else { Direction::Outgoing };
+        self.degrees.read().unwrap().top(relation, direction, limit)
+    }
+
+    /// Returns the number of nodes with each degree along a relation in a direction, leaving out
+    /// nodes without such edges. Read from the degree index like
+    /// [`RwLockedGraph::top_by_degree`].
+    pub fn degree_histogram(&self, relation: RelationId, direction: Direction) -> BTreeMap<u64, u64> {
+        let direction = if self.directed { direction } else { Direction::Outgoing };
+        self.degrees.read().unwrap().histogram(relation, direction)
     }
 
     /// Checks that a node exists.
-    pub fn get_node(&self, source: u32) -> Option<u32> {
+    pub fn get_node(&self, source: NodeId) -> Option<NodeId> {
         Some(source).filter(|&s| self.nodes.read().unwrap().contains_key(&s))
     }
 
+    /// Resolves an external key to its internal ID without assigning a new one. Without ID
+    /// mapping, only integer keys that fit a [`NodeId`] resolve.
+    pub fn resolve_key(&self, key: &NodeKey) -> Option<NodeId> {
+        match &self.id_map {
+            Some(id_map) => id_map.read().unwrap().get(key),
+            None => match key {
+                NodeKey::Int(id) => node_id::from_u64(*id),
+                NodeKey::Str(_) => None,
+            },
+        }
+    }
+
+    /// Checks that [`RwLockedGraph::intern_key`] would accept a key, without assigning it an ID.
+    pub fn accepts_key(&self, key: &NodeKey) -> bool {
+        self.id_map.is_some() || self.resolve_key(key).is_some()
+    }
+
+    /// Resolves an external key to its internal ID, assigning the next dense ID to new keys
+    /// when ID mapping is enabled.
+    pub fn intern_key(&self, key: &NodeKey) -> Option<NodeId> {
+        match &self.id_map {
+            Some(id_map) => Some(id_map.write().unwrap().get_or_insert(key)),
+            None => self.resolve_key(key),
+        }
+    }
+
+    /// Sets the unix timestamp of an edge, in seconds.
+    pub fn set_edge_timestamp(&self, source: NodeId, target: NodeId, relation: RelationId, timestamp: u64) {
+        let timestamp = PropertyValue::Int(timestamp.try_into().unwrap_or(i64::MAX));
+        self.set_edge_property(source, target, relation, TIMESTAMP_PROPERTY, timestamp);
+    }
+
+    /// Keeps only the neighbors of a node whose edge timestamp lies within `since..=until`.
+    /// Edges without a timestamp are dropped.
+    pub fn retain_edges_within(
+        &self,
+        node: NodeId,
+        neighbors: &mut NodeSet,
+        relation: RelationId,
+        direction: Direction,
+        since: Option<u64>,
+        until: Option<u64>,
+    ) {
+        let window = timestamp_window(since, until);
+        let edge_properties = self.edge_properties.read().unwrap();
+        let outside: Vec<NodeId> = neighbors
+            .iter()
+            .filter(|&neighbor| !self.stamped_within(&edge_properties, node, neighbor, relation, direction, &window))
+            .collect();
+
+        for neighbor in outside {
+            neighbors.remove(neighbor);
+        }
+    }
+
+    /// Checks that the edge between a node and one of its neighbors has a timestamp within a
+    /// window.
+    fn stamped_within(
+        &self,
+        edge_properties: &EdgePropertyStore,
+        node: NodeId,
+        neighbor: NodeId,
+        relation: RelationId,
+        direction: Direction,
+        window: &RangeInclusive<i64>,
+    ) -> bool {
+        let edge = match direction {
+            Direction::Outgoing => self.edge_key(node, neighbor, relation),
+            Direction::Incoming => self.edge_key(neighbor, node, relation),
+        };
+        matches!(
+            edge_properties.get(&edge, TIMESTAMP_PROPERTY),
+            Some(PropertyValue::Int(timestamp)) if window.contains(timestamp)
+        )
+    }
+
+    /// Lists a page or a random sample of the neighbors of a node, reading its neighbor set in
+    /// place rather than copying it. Expired edges and the neighbors the filter rejects are left
+    /// out. Undirected graphs are read along their edges, whatever the direction.
+    pub fn list_neighbors(
+        &self,
+        node: NodeId,
+        relation: RelationId,
+        direction: Direction,
+        filter: &NeighborFilter,
+        selection: &Selection,
+    ) -> NeighborList {
+        let direction = if self.directed { direction } else { Direction::Outgoing };
+        let nodes = self.nodes.read().unwrap();
+        let Some(node_map) = nodes.get(&node) else {
+            return NeighborList::default();
+        };
+        let edges = match direction {
+            Direction::Outgoing => node_map.outgoing_edges.read().unwrap(),
+            Direction::Incoming => node_map.incoming_edges.read().unwrap(),
+        };
+        let Some(neighbors) = edges.get(&relation) else {
+            return NeighborList::default();
+        };
+
+        let edge_expiries = self.edge_expiries.read().unwrap();
+        let expired: NodeSet = if edge_expiries.is_empty() {
+            NodeSet::new()
+        } else {
+            self.expired_neighbors(&edge_expiries, node, relation, direction, change_log::now()).into_iter().collect()
+        };
+        let node_data = self.node_data.read().unwrap();
+        let labeled = |neighbor| match filter.label {
+            Some(label) => node_data.label_members(label).is_some_and(|members| members.contains(neighbor)),
+            None => true,
+        };
+        let edge_properties = self.edge_properties.read().unwrap();
+        let window = timestamp_window(filter.since, filter.until);
+        let stamped = |neighbor| match (filter.since, filter.until) {
+            (None, None) => true,
+            _ => self.stamped_within(&edge_properties, node, neighbor, relation, direction, &window),
+        };
+
+        neighbor_list::select(neighbors, selection, |neighbor| {
+            !expired.contains(neighbor) && labeled(neighbor) && stamped(neighbor)
+        })
+    }
+
+    /// Returns the number of changes logged so far. Changes logged from then on can be replayed
+    /// with [`RwLockedGraph::replay_changes_onto`].
+    pub fn change_offset(&self) -> usize {
+        self.change_log.read().unwrap().len()
+    }
+
+    /// Applies the changes logged from `offset` on to another graph, along with the properties
+    /// of the added edges, e.g. to catch up a graph that was reloaded in the background. Node
+    /// labels and properties are not part of input files, so all of them are copied, and so are
+    /// the expiries of edges both graphs have. Nodes and relations are matched by their external
+    /// keys and names. Nodes the other graph lacks are marked for flushing, so that the next flush
+    /// of the other graph deletes their rows when both share a database.
+    pub fn replay_changes_onto(&self, graph: &RwLockedGraph, offset: usize) {
+        let translate = |nid| {
+            graph
+                .intern_key(&self.external_key(nid))
+                .expect("keys of a graph with the same settings are valid")
+        };
+
+        let change_log = self.change_log.read().unwrap();
+        let entries = change_log.entries_from(offset);
+        for entry in entries {
+            let (source, target) = (translate(entry.source), translate(entry.target));
+            let relation = graph.intern_relation(&self.relation_name(entry.relation).unwrap());
+            match entry.action {
+                GraphAction::AddEdge => {
+                    graph.add_edge(source, target, relation);
+
+                    let edge = (entry.source, entry.target, entry.relation);
+                    for (key, value) in self.edge_properties.read().unwrap().get_all(&edge) {
+                        graph.set_edge_property(source, target, relation, &key, value);
+                    }
+                }
+                GraphAction::RemoveEdge => graph.remove_edge(source, target, relation),
+            }
+        }
+
+        // temporary edges that are also in the new input file keep expiring
+        for ((source, target, relation), expires_at) in self.edge_expiries.read().unwrap().iter() {
+            let edge = (
+                graph.resolve_key(&self.external_key(source)),
+                graph.resolve_key(&self.external_key(target)),
+                self.relation_name(relation).and_then(|name| graph.resolve_relation(&name)),
+            );
+            if let (Some(source), Some(target), Some(relation)) = edge {
+                if graph.has_edge(source, target, relation) {
+                    graph.set_edge_expiry(source, target, relation, expires_at);
+                }
+            }
+        }
+
+        let node_data = self.node_data.read().unwrap();
+        for (label, members) in node_data.labels() {
+            for nid in members.iter() {
+                graph.add_node_label(translate(nid), label);
+            }
+        }
+        for (nid, properties) in node_data.properties() {
+            for (key, value) in properties {
+                graph.set_node_property(translate(nid), key, Some(value.clone()));
+            }
+        }
+
+        // nodes gone from the new input file would otherwise keep their rows in the database
+        let kept = graph.nodes.read().unwrap();
+        let mut reloaded_log = graph.change_log.write().unwrap();
+        for &nid in self.nodes.read().unwrap().keys() {
+            let nid = translate(nid);
+            if !kept.contains_key(&nid) {
+                reloaded_log.mark_base(nid);
+            }
+        }
+        info!("Replayed {} changes onto reloaded graph", entries.len());
+    }
+
+    /// Returns a counter of the rows read by the running or last load, which can be watched
+    /// from another thread while the graph loads.
+    pub fn load_progress(&self) -> Arc<AtomicUsize> {
+        self.load_progress.clone()
+    }
+
+    /// Returns the changes applied after a unix time, in seconds, oldest first. Flushed changes
+    /// older than the retention of the change log are no longer returned.
+    pub fn get_changes_after(&self, time: u64) -> Vec<ChangeLogEntry> {
+        self.change_log.read().unwrap().entries_after(time).to_vec()
+    }
+
+    /// Rebuilds the adjacency as it was at a unix time, in seconds, by undoing every logged change
+    /// applied after it. Only edges are played back; labels and properties are left out. Returns
+    /// `None` when changes since then were already dropped from the change log.
+    pub fn replay_until(&self, time: u64) -> Option<RwLockedGraph> {
+        // writers hold the nodes while they log their change, so take the nodes first
+        let current = self.nodes.read().unwrap();
+        let change_log = self.change_log.read().unwrap();
+        if !change_log.covers(time) {
+            return None;
+        }
+        let mut nodes = current.clone();
+        drop(current);
+        for entry in change_log.entries_after(time).iter().rev() {
+            match (entry.action, entry.existed) {
+                (GraphAction::AddEdge, false) => {
+                    self.delete_from(&mut nodes, entry.source, entry.target, entry.relation);
+                }
+                (GraphAction::RemoveEdge, true) => {
+                    self.insert_into(&mut nodes, entry.source, entry.target, entry.relation);
+                }
+                _ => {}
+            }
+        }
+
+        Some(RwLockedGraph {
+            degrees: RwLock::new(index_degrees(&nodes)),
+            nodes: RwLock::new(nodes),
+            is_loaded: RwLock::new(true),
+            relations: RwLock::new(self.relations.read().unwrap().clone()),
+            id_map: self
+                .id_map
+                .as_ref()
+                .map(|id_map| RwLock::new(id_map.read().unwrap().clone())),
+            directed: self.directed,
+            ..RwLockedGraph::new(0)
+        })
+    }
+
+    /// Sets a property of an edge, e.g. `created_at` or `source_app`.
+    pub fn set_edge_property(
+        &self,
+        source: NodeId,
+        target: NodeId,
+        relation: RelationId,
+        key: &str,
+        value: PropertyValue,
+    ) {
+        self.edge_properties
+            .write()
+            .unwrap()
+            .set(self.edge_key(source, target, relation), key, value);
+    }
+
+    /// Returns a single property of an edge.
+    pub fn get_edge_property(&self, source: NodeId, target: NodeId, relation: RelationId, key: &str) -> Option<PropertyValue> {
+        self.edge_properties
+            .read()
+            .unwrap()
+            .get(&self.edge_key(source, target, relation), key)
+            .cloned()
+    }
+
+    /// Returns all properties of an edge, sorted by key.
+    pub fn get_edge_properties(&self, source: NodeId, target: NodeId, relation: RelationId) -> BTreeMap<String, PropertyValue> {
+        self.edge_properties.read().unwrap().get_all(&self.edge_key(source, target, relation))
+    }
+
+    /// Adds a label to a node.
+    pub fn add_node_label(&self, nid: NodeId, label: &str) {
+        self.node_data.write().unwrap().add_label(nid, label);
+    }
+
+    /// Removes a label from a node.
+    pub fn remove_node_label(&self, nid: NodeId, label: &str) {
+        self.node_data.write().unwrap().remove_label(nid, label);
+    }
+
+    /// Returns the labels of a node.
+    pub fn get_node_labels(&self, nid: NodeId) -> Vec<String> {
+        self.node_data.read().unwrap().labels_of(nid)
+    }
+
+    /// Sets a property of a node, or removes it when `value` is `None`.
+    pub fn set_node_property(&self, nid: NodeId, key: &str, value: Option<PropertyValue>) {
+        let mut node_data = self.node_data.write().unwrap();
+        match value {
+            Some(value) => node_data.set_property(nid, key, value),
+            None => node_data.remove_property(nid, key),
+        }
+    }
+
+    /// Returns the properties of a node, sorted by key.
+    pub fn get_node_properties(&self, nid: NodeId) -> BTreeMap<String, PropertyValue> {
+        self.node_data
+            .read()
+            .unwrap()
+            .properties_of(nid)
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Keeps only the nodes of a set that carry a label.
+    pub fn retain_labeled(&self, nodes: &mut NodeSet, label: &str) {
+        match self.node_data.read().unwrap().label_members(label) {
+            Some(members) => *nodes &= members,
+            None => nodes.clear(),
+        }
+    }
+
+    /// Returns the ID of a relation without registering it. Unknown relations have no edges.
+    pub fn resolve_relation(&self, name: &str) -> Option<RelationId> {
+        self.relations.read().unwrap().get(name)
+    }
+
+    /// Returns the ID of a relation, registering the name if it has not been seen before.
+    pub fn intern_relation(&self, name: &str) -> RelationId {
+        self.relations.write().unwrap().get_or_insert(name)
+    }
+
+    /// Returns the name of a relation.
+    pub fn relation_name(&self, relation: RelationId) -> Option<String> {
+        self.relations.read().unwrap().name(relation).map(str::to_string)
+    }
+
+    /// Translates an internal ID back to the external key it was assigned to.
+    pub fn external_key(&self, nid: NodeId) -> NodeKey {
+        match &self.id_map {
+            Some(id_map) => id_map
+                .read()
+                .unwrap()
+                .key(nid)
+                .cloned()
+                .unwrap_or(NodeKey::Int(node_id::to_u64(nid))),
+            None => NodeKey::Int(node_id::to_u64(nid)),
+        }
+    }
+}
+
+/// Moves a `nodes` table written before relations were introduced, keyed by `nid` alone, to the
+/// `(nid, relation)` layout. Its rows all belong to the default relation.
+fn migrate_nodes_table(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let columns: Vec<String> = conn
+        .prepare("SELECT name FROM pragma_table_info('nodes')")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    if columns.is_empty() || columns.iter().any(|column| column == "relation") {
+        return Ok(());
+    }
+
+    info!("Migrating nodes table to one row per relation");
+    conn.execute_batch(&format!(
+        "BEGIN;
+        ALTER TABLE nodes RENAME TO nodes_unrelated;
+        CREATE TABLE nodes (
+            nid INTEGER NOT NULL,
+            relation TEXT NOT NULL,
+            outgoing BLOB NOT NULL,
+            incoming BLOB NOT NULL,
+            PRIMARY KEY (nid, relation)
+        );
+        INSERT INTO nodes (nid, relation, outgoing, incoming)
+            SELECT nid, '{}', outgoing, incoming FROM nodes_unrelated;
+        DROP TABLE nodes_unrelated;
+        COMMIT;",
+        DEFAULT_RELATION
+    ))
 }
 
 impl RwLockedGraph {
-    /// Flushes updated_nodes.
+    /// Flushes the nodes changed since the last flush.
     pub fn flush_updates(&self) -> Result<(), rusqlite::Error> {
-        let conn = Connection::open("data/raphle.db")?;
+        let conn = Connection::open(&self.db_path)?;
         conn.pragma_update(None, "journal_mode", &"WAL")?;
         conn.pragma_update(None, "synchronous", &"NORMAL")?;
+        migrate_nodes_table(&conn)?;
 
+        // Every relation of a node is stored as its own row
         match conn.execute(
             "CREATE TABLE IF NOT EXISTS nodes (
-                nid INTEGER PRIMARY KEY,
+                nid INTEGER NOT NULL,
+                relation TEXT NOT NULL,
                 outgoing BLOB NOT NULL,
-                incoming BLOB NOT NULL
+                incoming BLOB NOT NULL,
+                PRIMARY KEY (nid, relation)
             )",
             [],
         ) {
@@ -150,11 +1144,17 @@ impl RwLockedGraph {
         }
 
         let mut stmt = conn.prepare(
-            "INSERT OR REPLACE INTO nodes (nid, outgoing, incoming) VALUES (?, ?, ?)",
+            "INSERT OR REPLACE INTO nodes (nid, relation, outgoing, incoming) VALUES (?, ?, ?, ?)",
         )?;
+        let mut delete_stmt = conn.prepare("DELETE FROM nodes WHERE nid = ?")?;
 
-        let updated_nodes = self.updated_nodes.read().unwrap().clone();
+        let (updated_nodes, flushed_offset) = {
+            let change_log = self.change_log.read().unwrap();
+            (change_log.unflushed_nodes(), change_log.len())
+        };
         let nodes = self.nodes.read().unwrap();
+        let relations = self.relations.read().unwrap();
+        let empty = NodeSet::new();
 
         let mut num_updated = 0;
         let total_updated = updated_nodes.len();
@@ -164,31 +1164,161 @@ impl RwLockedGraph {
                 info!("Updating node {}/{}", num_updated, total_updated);
             }
 
-            let node = nodes.get(&nid).unwrap();
+            // removed nodes lose their rows
+            let Some(node) = nodes.get(&nid) else {
+                delete_stmt.execute([node_id::to_sql(nid)])?;
+                num_updated += 1;
+                continue;
+            };
             let outgoing = node.outgoing_edges.read().unwrap();
             let incoming = node.incoming_edges.read().unwrap();
-            let mut outgoing_bytes = vec![];
-            let mut incoming_bytes = vec![];
 
-            outgoing.serialize_into(&mut outgoing_bytes).unwrap();
-            incoming.serialize_into(&mut incoming_bytes).unwrap();
+            for (rid, relation) in relations.iter() {
+                let node_outgoing = outgoing.get(&rid);
+                let node_incoming = incoming.get(&rid);
+                if node_outgoing.is_none() && node_incoming.is_none() {
+                    continue;
+                }
+
+                let mut outgoing_bytes = vec![];
+                let mut incoming_bytes = vec![];
 
-            match stmt.execute((nid, outgoing_bytes, incoming_bytes)) {
-                Ok(_) => {}
-                Err(e) => {
+                node_outgoing.unwrap_or(&empty).serialize_into(&mut outgoing_bytes).unwrap();
+                node_incoming.unwrap_or(&empty).serialize_into(&mut incoming_bytes).unwrap();
+
+                if let Err(e) = stmt.execute((node_id::to_sql(nid), relation, outgoing_bytes, incoming_bytes)) {
                     error!("Error inserting row: {:?}", e);
+                    return Err(e);
                 }
             }
             num_updated += 1;
         }
 
-        self.updated_nodes.write().unwrap().clear();
+        // only once every row made it, so failed nodes are flushed again next time
+        self.change_log.write().unwrap().mark_flushed(flushed_offset);
+
+        self.flush_node_data(&conn)?;
+        self.flush_edge_properties(&conn)?;
+
+        if let Some(id_map) = &self.id_map {
+            self.flush_id_map(&conn, id_map)?;
+        }
+        Ok(())
+    }
+
+    /// Persists changed label bitmaps and node properties next to the `nodes` table.
+    fn flush_node_data(&self, conn: &Connection) -> Result<(), rusqlite::Error> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS node_labels (
+                label TEXT PRIMARY KEY,
+                members BLOB NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS node_properties (
+                nid INTEGER NOT NULL,
+                key TEXT NOT NULL,
+                value NOT NULL,
+                PRIMARY KEY (nid, key)
+            )",
+            [],
+        )?;
+
+        let mut node_data = self.node_data.write().unwrap();
+        let (dirty_labels, dirty_properties) = node_data.dirty();
+
+        let mut stmt = conn.prepare("INSERT OR REPLACE INTO node_labels (label, members) VALUES (?, ?)")?;
+        for label in dirty_labels.iter() {
+            let mut members_bytes = vec![];
+            if let Some(members) = node_data.label_members(label) {
+                members.serialize_into(&mut members_bytes).unwrap();
+            }
+            stmt.execute((label, members_bytes))?;
+        }
+
+        let mut delete_stmt = conn.prepare("DELETE FROM node_properties WHERE nid = ?")?;
+        let mut insert_stmt = conn.prepare("INSERT INTO node_properties (nid, key, value) VALUES (?, ?, ?)")?;
+        for nid in dirty_properties.iter() {
+            delete_stmt.execute([node_id::to_sql(nid)])?;
+            for (key, value) in node_data.properties_of(nid).into_iter().flatten() {
+                insert_stmt.execute((node_id::to_sql(nid), key, value))?;
+            }
+        }
+
+        node_data.clear_dirty();
         Ok(())
     }
 
-    /// Loads from a TSV file given a path.
-    pub fn load_from_csv(&mut self, path: &str, delimiter: Option<u8>) -> std::io::Result<()> {
+    /// Persists the properties of changed edges, one row per edge and property.
+    fn flush_edge_properties(&self, conn: &Connection) -> Result<(), rusqlite::Error> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS edge_properties (
+                source INTEGER NOT NULL,
+                target INTEGER NOT NULL,
+                relation TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value NOT NULL,
+                PRIMARY KEY (source, target, relation, key)
+            )",
+            [],
+        )?;
+
+        let mut edge_properties = self.edge_properties.write().unwrap();
+        let relations = self.relations.read().unwrap();
+
+        let mut delete_stmt = conn.prepare(
+            "DELETE FROM edge_properties WHERE source = ? AND target = ? AND relation = ?",
+        )?;
+        let mut insert_stmt = conn.prepare(
+            "INSERT INTO edge_properties (source, target, relation, key, value) VALUES (?, ?, ?, ?, ?)",
+        )?;
+        for edge in edge_properties.dirty().iter() {
+            let (source, target, rid) = *edge;
+            let relation = relations.name(rid).unwrap();
+            delete_stmt.execute((node_id::to_sql(source), node_id::to_sql(target), relation))?;
+            for (key, value) in edge_properties.get_all(edge) {
+                insert_stmt.execute((node_id::to_sql(source), node_id::to_sql(target), relation, key, value))?;
+            }
+        }
+
+        edge_properties.clear_dirty();
+        Ok(())
+    }
+
+    /// Persists the external keys assigned since the last flush next to the `nodes` table.
+    fn flush_id_map(&self, conn: &Connection, id_map: &RwLock<IdMap>) -> Result<(), rusqlite::Error> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS node_keys (
+                nid INTEGER PRIMARY KEY,
+                key TEXT NOT NULL UNIQUE
+            )",
+            [],
+        )?;
+
+        let mut stmt = conn.prepare("INSERT OR REPLACE INTO node_keys (nid, key) VALUES (?, ?)")?;
+        let mut id_map = id_map.write().unwrap();
+        for nid in id_map.unflushed().iter() {
+            let key = id_map.key(nid).unwrap().to_string();
+            stmt.execute((node_id::to_sql(nid), key))?;
+        }
+
+        info!("Persisted {} new node keys", id_map.unflushed().len());
+        id_map.clear_unflushed();
+        Ok(())
+    }
+
+    /// Loads edges of a relation from a TSV file given a path. Columns after the source and
+    /// target are loaded as edge properties named by `property_columns`, in order.
+    pub fn load_from_csv(
+        &mut self,
+        path: &str,
+        delimiter: Option<u8>,
+        relation: &str,
+        property_columns: &[&str],
+    ) -> std::io::Result<()> {
         let file = File::open(path)?;
+        let relation = self.intern_relation(relation);
         let mut reader_builder = ReaderBuilder::new();
            
 
@@ -198,8 +1328,10 @@ impl RwLockedGraph {
             None => &mut reader_builder,
         };
 
-        let mut rows = reader_builder.has_headers(false).from_reader(BufReader::new(file));
+        // property columns may be left off of rows that have no properties
+        let mut rows = reader_builder.has_headers(false).flexible(true).from_reader(BufReader::new(file));
         let mut row_count = 0;
+        let mut duplicate_count = 0;
             
         for res in rows.records() {
             row_count += 1;
@@ -209,22 +1341,37 @@ impl RwLockedGraph {
             }
 
             let rec = res?;
+            self.load_progress.store(row_count, Ordering::Relaxed);
             
-            let source: u32 = rec.get(0)
-                .unwrap()
-                .parse::<u32>()
-                .unwrap();
-            let target: u32 = rec.get(1)
-                .unwrap()
-                .parse::<u32>()
-                .unwrap();
+            let source = self.intern_column(&rec, 0, row_count)?;
+            let target = self.intern_column(&rec, 1, row_count)?;
+
+            if self.load_edge(source, target, relation) {
+                duplicate_count += 1;
+            }
 
-            self.add_edge(source, target);
+            for (column, key) in property_columns.iter().enumerate() {
+                if let Some(raw) = rec.get(column + 2).filter(|raw| !raw.is_empty()) {
+                    self.set_edge_property(source, target, relation, key, PropertyValue::from(raw));
+                }
+            }
         }
 
         *self.is_loaded.write().unwrap() = true;
         info!("Loaded graph with {} edges", row_count); // should be user count
+        if duplicate_count > 0 {
+            info!("Skipped {} duplicate edges", duplicate_count);
+        }
 
         Ok(())
     }
+
+    /// Interns the node key found in a column of an input record. Fails with
+    /// [`std::io::ErrorKind::InvalidData`] when the field is missing or empty, or holds a key the
+    /// graph cannot take. The key itself is left out of the error.
+    fn intern_column(&self, rec: &csv::StringRecord, column: usize, row: usize) -> std::io::Result<NodeId> {
+        let invalid = |reason| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{} node key in column {} of row {}", reason, column, row));
+        let raw = rec.get(column).filter(|raw| !raw.is_empty()).ok_or_else(|| invalid("missing"))?;
+        self.intern_key(&NodeKey::from(raw)).ok_or_else(|| invalid("invalid"))
+    }
 }
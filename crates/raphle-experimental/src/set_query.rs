@@ -0,0 +1,122 @@
+use crate::node_id::{NodeId, NodeSet};
+use crate::relation::RelationId;
+use crate::rwlocked_graph::{AdjacencyView, Direction, RwLockedGraph};
+use crate::traversal::TraversalDirection;
+
+/// An expression over sets of nodes, evaluated with bitmap operations.
+#[derive(Clone, Debug)]
+pub enum SetExpr {
+    /// The neighbors of a node along a relation.
+    Neighbors {
+        node: NodeId,
+        relation: RelationId,
+        direction: TraversalDirection,
+    },
+    /// A fixed set of nodes.
+    Nodes(NodeSet),
+    Union(Vec<SetExpr>),
+    /// The nodes in every operand. An intersection without operands is empty.
+    Intersect(Vec<SetExpr>),
+    /// The nodes of the first operand that are not in the second.
+    Difference(Box<SetExpr>, Box<SetExpr>),
+}
+
+impl SetExpr {
+    /// Evaluates the expression under a single read lock of the graph.
+    pub fn evaluate(&self, graph: &RwLockedGraph) -> NodeSet {
+        self.evaluate_in(&graph.read_adjacency())
+    }
+
+    fn evaluate_in(&self, adjacency: &AdjacencyView) -> NodeSet {
+        match self {
+            SetExpr::Neighbors {
+                node,
+                relation,
+                direction,
+            } => neighbors(adjacency, *node, *relation, *direction),
+            SetExpr::Nodes(nodes) => nodes.clone(),
+            SetExpr::Union(operands) => {
+                let mut union = NodeSet::new();
+                for operand in operands {
+                    union |= operand.evaluate_in(adjacency);
+                }
+                union
+            }
+            SetExpr::Intersect(operands) => {
+                let Some((first, rest)) = operands.split_first() else {
+                    return NodeSet::new();
+                };
+                let mut intersection = first.evaluate_in(adjacency);
+                for operand in rest {
+                    if intersection.is_empty() {
+                        break;
+                    }
+                    intersection &= operand.evaluate_in(adjacency);
+                }
+                intersection
+            }
+            SetExpr::Difference(left, right) => {
+                let mut difference = left.evaluate_in(adjacency);
+                if !difference.is_empty() {
+                    difference -= right.evaluate_in(adjacency);
+                }
+                difference
+            }
+        }
+    }
+}
+
+fn neighbors(
+    adjacency: &AdjacencyView,
+    node: NodeId,
+    relation: RelationId,
+    direction: TraversalDirection,
+) -> NodeSet {
+    match direction {
+        TraversalDirection::Out => adjacency.neighbors(node, relation, Direction::Outgoing),
+        TraversalDirection::In => adjacency.neighbors(node, relation, Direction::Incoming),
+        TraversalDirection::Both => {
+            let mut neighbors = adjacency.neighbors(node, relation, Direction::Outgoing);
+            neighbors |= adjacency.neighbors(node, relation, Direction::Incoming);
+            neighbors
+        }
+    }
+}
+
+/// Nodes in the neighbor sets of two nodes, with a sample of the most followed ones for display.
+pub struct Overlap {
+    pub nodes: NodeSet,
+    /// Up to the requested number of nodes, by descending number of incoming edges.
+    pub sample: Vec<NodeId>,
+}
+
+/// Intersects the `left_direction` neighbors of `left` with the `right_direction` neighbors of
+/// `right` under a single read lock. Outgoing neighbors on both sides give the accounts two
+/// users both follow; outgoing neighbors of a viewer and incoming ones of an account give the
+/// accounts the viewer follows that also follow it.
+pub fn overlap(
+    graph: &RwLockedGraph,
+    (left, left_direction): (NodeId, Direction),
+    (right, right_direction): (NodeId, Direction),
+    relation: RelationId,
+    sample_size: usize,
+) -> Overlap {
+    let adjacency = graph.read_adjacency();
+    let mut nodes = adjacency.neighbors(left, relation, left_direction);
+    if !nodes.is_empty() {
+        nodes &= adjacency.neighbors(right, relation, right_direction);
+    }
+
+    let mut ranked: Vec<(u64, NodeId)> = nodes
+        .iter()
+        .map(|nid| (adjacency.degree(nid, relation, Direction::Incoming), nid))
+        .collect();
+    ranked.sort_unstable_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    let sample = ranked
+        .into_iter()
+        .take(sample_size)
+        .map(|(_, nid)| nid)
+        .collect();
+
+    Overlap { nodes, sample }
+}
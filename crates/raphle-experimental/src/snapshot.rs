@@ -0,0 +1,145 @@
+use hashbrown::HashMap;
+
+use crate::node_id::NodeId;
+use crate::relation::RelationId;
+use crate::rwlocked_graph::{Direction, RwLockedGraph};
+use crate::weighted_path::DEFAULT_WEIGHT;
+
+/// Neighbor lists in compressed sparse row form: the neighbors of the node at index `i` are
+/// `neighbors[offsets[i]..offsets[i + 1]]`.
+#[derive(Clone, Default)]
+struct Csr {
+    offsets: Vec<usize>,
+    neighbors: Vec<u32>,
+}
+
+impl Csr {
+    fn neighbors(&self, index: usize) -> &[u32] {
+        &self.neighbors[self.offsets[index]..self.offsets[index + 1]]
+    }
+}
+
+/// An immutable copy of the edges of one relation, taken under a single read lock, for
+/// whole-graph analytics to run on without holding the graph. Nodes are addressed by their
+/// index, which follows ascending node ID.
+pub struct Snapshot {
+    nodes: Vec<NodeId>,
+    outgoing: Csr,
+    incoming: Csr,
+    /// Weight of each outgoing edge, in the order of `outgoing`.
+    weights: Option<Vec<f64>>,
+    directed: bool,
+}
+
+impl Snapshot {
+    /// Copies the edges of `relation`, leaving out expired ones. In undirected graphs the
+    /// incoming neighbors of a node are its outgoing ones.
+    pub fn of(graph: &RwLockedGraph, relation: RelationId) -> Self {
+        let adjacency = graph.read_adjacency();
+        let mut nodes: Vec<NodeId> = adjacency.nodes().collect();
+        nodes.sort_unstable();
+        let index: HashMap<NodeId, u32> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, &nid)| (nid, i as u32))
+            .collect();
+
+        let copy = |direction| {
+            let mut csr = Csr {
+                offsets: Vec::with_capacity(nodes.len() + 1),
+                neighbors: Vec::new(),
+            };
+            csr.offsets.push(0);
+            for &nid in &nodes {
+                let neighbors = adjacency.neighbors(nid, relation, direction);
+                csr.neighbors
+                    .extend(neighbors.iter().filter_map(|neighbor| index.get(&neighbor)));
+                csr.offsets.push(csr.neighbors.len());
+            }
+            csr
+        };
+        let outgoing = copy(Direction::Outgoing);
+        let incoming = if graph.is_directed() {
+            copy(Direction::Incoming)
+        } else {
+            outgoing.clone()
+        };
+
+        Snapshot {
+            nodes,
+            outgoing,
+            incoming,
+            weights: None,
+            directed: graph.is_directed(),
+        }
+    }
+
+    /// Copies the `weight_property` of every outgoing edge as its weight. Edges without a
+    /// non-negative number there weigh [`DEFAULT_WEIGHT`].
+    pub fn weighted(
+        mut self,
+        graph: &RwLockedGraph,
+        relation: RelationId,
+        weight_property: &str,
+    ) -> Self {
+        let mut weights = Vec::with_capacity(self.outgoing.neighbors.len());
+        for i in 0..self.len() {
+            for &neighbor in self.outgoing(i) {
+                let weight = graph
+                    .get_edge_property(
+                        self.node(i),
+                        self.node(neighbor as usize),
+                        relation,
+                        weight_property,
+                    )
+                    .and_then(|weight| weight.as_f64())
+                    .filter(|weight| weight.is_finite() && *weight >= 0.0)
+                    .unwrap_or(DEFAULT_WEIGHT);
+                weights.push(weight);
+            }
+        }
+        self.weights = Some(weights);
+        self
+    }
+
+    /// Whether the snapshot was taken of a directed graph. Undirected snapshots hold every
+    /// edge from both ends.
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the node at an index.
+    pub fn node(&self, index: usize) -> NodeId {
+        self.nodes[index]
+    }
+
+    /// Returns the index of a node, if it is in the snapshot.
+    pub fn index_of(&self, nid: NodeId) -> Option<usize> {
+        self.nodes.binary_search(&nid).ok()
+    }
+
+    /// Returns the indices of the outgoing neighbors of the node at an index.
+    pub fn outgoing(&self, index: usize) -> &[u32] {
+        self.outgoing.neighbors(index)
+    }
+
+    /// Returns the weights of the outgoing edges of the node at an index, in the order of
+    /// [`Snapshot::outgoing`], if the snapshot is weighted.
+    pub fn outgoing_weights(&self, index: usize) -> Option<&[f64]> {
+        let weights = self.weights.as_ref()?;
+        Some(&weights[self.outgoing.offsets[index]..self.outgoing.offsets[index + 1]])
+    }
+
+    /// Returns the indices of the incoming neighbors of the node at an index.
+    pub fn incoming(&self, index: usize) -> &[u32] {
+        self.incoming.neighbors(index)
+    }
+}
@@ -0,0 +1,268 @@
+use serde::Deserialize;
+
+use crate::node_id::{NodeId, NodeSet};
+use crate::relation::RelationId;
+use crate::rwlocked_graph::{Direction, RwLockedGraph};
+
+/// Direction in which a traversal follows edges. Undirected graphs are always traversed
+/// along their edges, whatever the direction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraversalDirection {
+    #[default]
+    Out,
+    In,
+    Both,
+}
+
+/// Nodes within a number of hops of a start node, which itself is left out.
+pub struct Neighborhood {
+    /// Nodes by the hop at which they were first reached, so `layers[0]` holds the direct
+    /// neighbors.
+    pub layers: Vec<NodeSet>,
+    /// Whether the traversal stopped early because it reached its node limit.
+    pub truncated: bool,
+}
+
+impl Neighborhood {
+    /// Returns the nodes of every layer.
+    pub fn nodes(&self) -> NodeSet {
+        let mut nodes = NodeSet::new();
+        for layer in &self.layers {
+            nodes |= layer;
+        }
+        nodes
+    }
+}
+
+/// Returns the union of the neighbors of every node in a frontier.
+pub fn expand(
+    graph: &RwLockedGraph,
+    frontier: &NodeSet,
+    relation: RelationId,
+    direction: TraversalDirection,
+) -> NodeSet {
+    if !graph.is_directed() {
+        return graph.union_neighbors(frontier, relation, Direction::Outgoing);
+    }
+
+    match direction {
+        TraversalDirection::Out => graph.union_neighbors(frontier, relation, Direction::Outgoing),
+        TraversalDirection::In => graph.union_neighbors(frontier, relation, Direction::Incoming),
+        TraversalDirection::Both => {
+            let mut neighbors = graph.union_neighbors(frontier, relation, Direction::Outgoing);
+            neighbors |= graph.union_neighbors(frontier, relation, Direction::Incoming);
+            neighbors
+        }
+    }
+}
+
+/// Collects the nodes within `depth` hops of a node with a frontier BFS, which expands the
+/// whole frontier of a hop with one union of adjacency bitmaps. Once `limit` nodes are reached,
+/// the last layer is cut down to the nodes with the lowest IDs and the traversal stops.
+pub fn neighborhood(
+    graph: &RwLockedGraph,
+    node: NodeId,
+    relation: RelationId,
+    direction: TraversalDirection,
+    depth: usize,
+    limit: Option<u64>,
+) -> Neighborhood {
+    let mut visited = NodeSet::new();
+    visited.insert(node);
+    let mut frontier = visited.clone();
+    let mut neighborhood = Neighborhood {
+        layers: Vec::new(),
+        truncated: false,
+    };
+
+    let mut found = 0;
+    for _ in 0..depth {
+        let mut layer = expand(graph, &frontier, relation, direction);
+        layer -= &visited;
+        if layer.is_empty() {
+            break;
+        }
+
+        if let Some(limit) = limit {
+            let room = limit.saturating_sub(found);
+            if room == 0 {
+                neighborhood.truncated = true;
+                break;
+            }
+            if layer.len() > room {
+                layer = layer.iter().take(room as usize).collect();
+                neighborhood.truncated = true;
+            }
+        }
+
+        found += layer.len();
+        visited |= &layer;
+        frontier = layer.clone();
+        neighborhood.layers.push(layer);
+        if neighborhood.truncated {
+            break;
+        }
+    }
+    neighborhood
+}
+
+/// Shortest paths between two nodes.
+pub struct ShortestPaths {
+    /// Number of edges on each path.
+    pub distance: usize,
+    /// Paths from the source to the target, including both.
+    pub paths: Vec<Vec<NodeId>>,
+}
+
+/// Finds up to `limit` shortest paths from `source` to `target` along outgoing edges with a
+/// bidirectional BFS, which expands the smaller of the frontiers growing from the source over
+/// outgoing bitmaps and from the target over incoming bitmaps. Returns `None` when the target
+/// is not reachable within `max_depth` hops.
+pub fn shortest_paths(
+    graph: &RwLockedGraph,
+    source: NodeId,
+    target: NodeId,
+    relation: RelationId,
+    max_depth: usize,
+    limit: usize,
+) -> Option<ShortestPaths> {
+    if source == target {
+        return Some(ShortestPaths {
+            distance: 0,
+            paths: vec![vec![source]],
+        });
+    }
+
+    let mut forward = vec![NodeSet::from_iter([source])];
+    let mut backward = vec![NodeSet::from_iter([target])];
+    let mut forward_visited = forward[0].clone();
+    let mut backward_visited = backward[0].clone();
+
+    for distance in 1..=max_depth {
+        let expand_forward = forward.last().unwrap().len() <= backward.last().unwrap().len();
+        let (layers, visited, other_layers, direction) = if expand_forward {
+            (
+                &mut forward,
+                &mut forward_visited,
+                &backward,
+                TraversalDirection::Out,
+            )
+        } else {
+            (
+                &mut backward,
+                &mut backward_visited,
+                &forward,
+                TraversalDirection::In,
+            )
+        };
+
+        let mut layer = expand(graph, layers.last().unwrap(), relation, direction);
+        layer -= &*visited;
+        if layer.is_empty() {
+            return None;
+        }
+        *visited |= &layer;
+
+        // Shortest paths can only meet in the newest layer of both sides
+        let mut meet = layer.clone();
+        meet &= other_layers.last().unwrap();
+        layers.push(layer);
+
+        if !meet.is_empty() {
+            return Some(ShortestPaths {
+                distance,
+                paths: join_paths(graph, &forward, &backward, &meet, relation, limit),
+            });
+        }
+    }
+    None
+}
+
+/// Joins the paths from the source to each meeting node with those from the meeting node to
+/// the target, until `limit` paths are found.
+fn join_paths(
+    graph: &RwLockedGraph,
+    forward: &[NodeSet],
+    backward: &[NodeSet],
+    meet: &NodeSet,
+    relation: RelationId,
+    limit: usize,
+) -> Vec<Vec<NodeId>> {
+    let mut paths = Vec::new();
+    if limit == 0 {
+        return paths;
+    }
+    for node in meet.iter() {
+        let remaining = limit - paths.len();
+        let heads = walk_layers(
+            graph,
+            forward,
+            node,
+            relation,
+            TraversalDirection::In,
+            remaining,
+        );
+        let tails = walk_layers(
+            graph,
+            backward,
+            node,
+            relation,
+            TraversalDirection::Out,
+            remaining,
+        );
+
+        for head in &heads {
+            for tail in &tails {
+                // tails run from the target to the meeting node, which ends the head already
+                let mut path = head.clone();
+                path.extend(tail.iter().rev().skip(1));
+                paths.push(path);
+                if paths.len() == limit {
+                    return paths;
+                }
+            }
+        }
+    }
+    paths
+}
+
+/// Returns up to `limit` paths from the first layer to `node`, which lies in the last layer,
+/// through one node of every layer. Each step back to an earlier layer follows `direction`.
+fn walk_layers(
+    graph: &RwLockedGraph,
+    layers: &[NodeSet],
+    node: NodeId,
+    relation: RelationId,
+    direction: TraversalDirection,
+    limit: usize,
+) -> Vec<Vec<NodeId>> {
+    let Some((_, earlier)) = layers.split_last() else {
+        return Vec::new();
+    };
+    let Some(previous) = earlier.last() else {
+        return vec![vec![node]];
+    };
+
+    let mut steps = expand(graph, &NodeSet::from_iter([node]), relation, direction);
+    steps &= previous;
+
+    let mut paths = Vec::new();
+    for step in steps.iter() {
+        for mut path in walk_layers(
+            graph,
+            earlier,
+            step,
+            relation,
+            direction,
+            limit - paths.len(),
+        ) {
+            path.push(node);
+            paths.push(path);
+        }
+        if paths.len() >= limit {
+            break;
+        }
+    }
+    paths
+}
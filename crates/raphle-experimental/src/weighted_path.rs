@@ -0,0 +1,214 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::time::Instant;
+
+use hashbrown::HashMap;
+
+use crate::node_id::NodeId;
+use crate::relation::RelationId;
+use crate::rwlocked_graph::RwLockedGraph;
+
+/// Cost of an edge without a numeric weight property.
+pub const DEFAULT_WEIGHT: f64 = 1.0;
+
+/// The cheapest path found from a source to a node.
+#[derive(Clone, Debug)]
+pub struct Route {
+    pub target: NodeId,
+    /// Sum of the edge weights along the path.
+    pub cost: f64,
+    /// Nodes from the source to the target, including both.
+    pub path: Vec<NodeId>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchError {
+    /// The search ran past its deadline.
+    TimedOut,
+    /// An edge weight is negative or not a number, which the search cannot handle.
+    InvalidWeight,
+}
+
+/// Where a search stops.
+enum Goal {
+    Target(NodeId),
+    Budget(f64),
+}
+
+/// A node waiting to be settled, ordered so the cheapest one is popped first.
+struct Candidate {
+    priority: f64,
+    cost: f64,
+    node: NodeId,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .total_cmp(&self.priority)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+/// Finds the cheapest path from `source` to `target` along outgoing edges, weighted by the
+/// numeric edge property `weight_property`. With a `heuristic` that never overestimates the
+/// remaining cost to the target, the search runs as A*; without one, as Dijkstra.
+pub fn cheapest_path(
+    graph: &RwLockedGraph,
+    source: NodeId,
+    target: NodeId,
+    relation: RelationId,
+    weight_property: &str,
+    heuristic: Option<&dyn Fn(NodeId) -> f64>,
+    deadline: Instant,
+) -> Result<Option<Route>, SearchError> {
+    let routes = search(
+        graph,
+        source,
+        relation,
+        weight_property,
+        Goal::Target(target),
+        heuristic,
+        deadline,
+    )?;
+    Ok(routes.into_iter().find(|route| route.target == target))
+}
+
+/// Finds the cheapest paths from `source` to every node reachable within a total cost of
+/// `budget` along outgoing edges, cheapest first. The source itself is left out.
+pub fn cheapest_paths_within(
+    graph: &RwLockedGraph,
+    source: NodeId,
+    relation: RelationId,
+    weight_property: &str,
+    budget: f64,
+    deadline: Instant,
+) -> Result<Vec<Route>, SearchError> {
+    let mut routes = search(
+        graph,
+        source,
+        relation,
+        weight_property,
+        Goal::Budget(budget),
+        None,
+        deadline,
+    )?;
+    routes.retain(|route| route.target != source);
+    Ok(routes)
+}
+
+/// Runs Dijkstra, or A* given a heuristic, and returns the routes to the settled nodes in the
+/// order they were settled.
+fn search(
+    graph: &RwLockedGraph,
+    source: NodeId,
+    relation: RelationId,
+    weight_property: &str,
+    goal: Goal,
+    heuristic: Option<&dyn Fn(NodeId) -> f64>,
+    deadline: Instant,
+) -> Result<Vec<Route>, SearchError> {
+    let estimate = |node| heuristic.map_or(0.0, |heuristic| heuristic(node));
+
+    let mut costs: HashMap<NodeId, f64> = HashMap::new();
+    let mut parents: HashMap<NodeId, NodeId> = HashMap::new();
+    let mut settled = Vec::new();
+    let mut queue = BinaryHeap::new();
+    costs.insert(source, 0.0);
+    queue.push(Candidate {
+        priority: estimate(source),
+        cost: 0.0,
+        node: source,
+    });
+
+    while let Some(Candidate { cost, node, .. }) = queue.pop() {
+        if Instant::now() > deadline {
+            return Err(SearchError::TimedOut);
+        }
+        // skip entries made stale by a cheaper path found later
+        if costs.get(&node).is_some_and(|&best| cost > best) {
+            continue;
+        }
+
+        settled.push((node, cost));
+        if matches!(goal, Goal::Target(target) if target == node) {
+            break;
+        }
+
+        for neighbor in graph.get_outgoing_edges(node, relation).iter() {
+            let weight = edge_weight(graph, node, neighbor, relation, weight_property)?;
+            let neighbor_cost = cost + weight;
+            if matches!(goal, Goal::Budget(budget) if neighbor_cost > budget) {
+                continue;
+            }
+            if costs
+                .get(&neighbor)
+                .is_some_and(|&best| best <= neighbor_cost)
+            {
+                continue;
+            }
+
+            costs.insert(neighbor, neighbor_cost);
+            parents.insert(neighbor, node);
+            queue.push(Candidate {
+                priority: neighbor_cost + estimate(neighbor),
+                cost: neighbor_cost,
+                node: neighbor,
+            });
+        }
+    }
+
+    Ok(settled
+        .into_iter()
+        .map(|(target, cost)| Route {
+            target,
+            cost,
+            path: trace_path(&parents, source, target),
+        })
+        .collect())
+}
+
+/// Returns the weight of an edge, or [`DEFAULT_WEIGHT`] if it has no numeric weight.
+fn edge_weight(
+    graph: &RwLockedGraph,
+    source: NodeId,
+    target: NodeId,
+    relation: RelationId,
+    weight_property: &str,
+) -> Result<f64, SearchError> {
+    let weight = graph
+        .get_edge_property(source, target, relation, weight_property)
+        .and_then(|weight| weight.as_f64())
+        .unwrap_or(DEFAULT_WEIGHT);
+    if weight.is_nan() || weight < 0.0 {
+        return Err(SearchError::InvalidWeight);
+    }
+    Ok(weight)
+}
+
+/// Follows the parents of a node back to the source.
+fn trace_path(parents: &HashMap<NodeId, NodeId>, source: NodeId, target: NodeId) -> Vec<NodeId> {
+    let mut path = vec![target];
+    let mut node = target;
+    while node != source {
+        node = parents[&node];
+        path.push(node);
+    }
+    path.reverse();
+    path
+}
@@ -0,0 +1,190 @@
+use raphle_experimental::batch::{Batch, BatchOp, FailedPrecondition, Precondition};
+use raphle_experimental::change_log;
+use raphle_experimental::node_data::PropertyValue;
+use raphle_experimental::node_id::NodeId;
+use raphle_experimental::relation::{RelationId, DEFAULT_RELATION};
+use raphle_experimental::rwlocked_graph::{Direction, RwLockedGraph};
+
+fn add(source: NodeId, target: NodeId, relation: RelationId) -> BatchOp {
+    BatchOp::AddEdge {
+        source,
+        target,
+        relation,
+        properties: vec![],
+        expires_at: None,
+    }
+}
+
+#[test]
+fn applies_every_operation() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 2, rel);
+    graph.add_edge(3, 4, rel);
+    let expires_at = change_log::now() + 3600;
+
+    let batch = Batch {
+        preconditions: vec![Precondition::EdgePresent {
+            source: 1,
+            target: 2,
+            relation: rel,
+        }],
+        ops: vec![
+            BatchOp::AddEdge {
+                source: 1,
+                target: 3,
+                relation: rel,
+                properties: vec![("weight".to_string(), PropertyValue::Int(2))],
+                expires_at: Some(expires_at),
+            },
+            BatchOp::RemoveEdge {
+                source: 1,
+                target: 2,
+                relation: rel,
+            },
+            BatchOp::RemoveNode { node: 4 },
+        ],
+    };
+    graph.apply_batch(&batch).unwrap();
+
+    assert!(graph.has_edge(1, 3, rel));
+    assert!(!graph.has_edge(1, 2, rel));
+    assert!(graph.get_outgoing_edges(3, rel).is_empty());
+    assert_eq!(
+        graph.get_edge_property(1, 3, rel, "weight"),
+        Some(PropertyValue::Int(2))
+    );
+    assert_eq!(graph.reap_expired(expires_at), 1);
+}
+
+#[test]
+fn applies_nothing_when_a_precondition_fails() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 2, rel);
+    let offset = graph.change_offset();
+
+    let batch = Batch {
+        preconditions: vec![
+            Precondition::EdgePresent {
+                source: 1,
+                target: 2,
+                relation: rel,
+            },
+            Precondition::EdgeAbsent {
+                source: 1,
+                target: 2,
+                relation: rel,
+            },
+        ],
+        ops: vec![
+            add(1, 3, rel),
+            BatchOp::RemoveEdge {
+                source: 1,
+                target: 2,
+                relation: rel,
+            },
+        ],
+    };
+
+    assert_eq!(
+        graph.apply_batch(&batch),
+        Err(FailedPrecondition { index: 1 })
+    );
+    assert!(graph.has_edge(1, 2, rel));
+    assert!(!graph.has_edge(1, 3, rel));
+    assert_eq!(graph.change_offset(), offset);
+}
+
+#[test]
+fn enforces_out_degree_limits() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 2, rel);
+    graph.add_edge(1, 3, rel);
+    let follow_limit = |limit| Batch {
+        preconditions: vec![Precondition::OutDegreeBelow {
+            node: 1,
+            relation: rel,
+            limit,
+        }],
+        ops: vec![add(1, 4, rel)],
+    };
+
+    assert!(graph.apply_batch(&follow_limit(2)).is_err());
+    graph.apply_batch(&follow_limit(3)).unwrap();
+    assert_eq!(graph.degree(1, rel, Direction::Outgoing), 3);
+}
+
+#[test]
+fn treats_expired_edges_as_absent() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 2, rel);
+    graph.add_edge(1, 3, rel);
+    let past = change_log::now() - 1;
+    graph.set_edge_expiry(1, 2, rel, past);
+    // an expiry without its edge must not make the degree go below zero
+    graph.set_edge_expiry(1, 9, rel, past);
+
+    let batch = Batch {
+        preconditions: vec![
+            Precondition::EdgeAbsent {
+                source: 1,
+                target: 2,
+                relation: rel,
+            },
+            Precondition::OutDegreeBelow {
+                node: 1,
+                relation: rel,
+                limit: 1,
+            },
+        ],
+        ops: vec![add(1, 2, rel)],
+    };
+    graph.apply_batch(&batch).unwrap();
+    assert!(graph.has_edge(1, 2, rel));
+
+    let empty = RwLockedGraph::new(16);
+    let rel = empty.intern_relation(DEFAULT_RELATION);
+    empty.set_edge_expiry(5, 6, rel, past);
+    let batch = Batch {
+        preconditions: vec![Precondition::OutDegreeBelow {
+            node: 5,
+            relation: rel,
+            limit: 1,
+        }],
+        ops: vec![],
+    };
+    empty.apply_batch(&batch).unwrap();
+}
+
+#[test]
+fn builds_operations_only_when_the_preconditions_hold() {
+    let graph = RwLockedGraph::with_id_mapping(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    let alice = graph.intern_key(&"alice".into()).unwrap();
+    let failing = [Precondition::OutDegreeBelow {
+        node: alice,
+        relation: rel,
+        limit: 0,
+    }];
+
+    let result = graph.apply_batch_with(&failing, || {
+        let bob = graph.intern_key(&"bob".into()).unwrap();
+        vec![add(alice, bob, graph.intern_relation("likes"))]
+    });
+    assert_eq!(result, Err(FailedPrecondition { index: 0 }));
+    assert_eq!(graph.resolve_key(&"bob".into()), None);
+    assert_eq!(graph.resolve_relation("likes"), None);
+
+    graph
+        .apply_batch_with(&[], || {
+            let bob = graph.intern_key(&"bob".into()).unwrap();
+            vec![add(alice, bob, graph.intern_relation("likes"))]
+        })
+        .unwrap();
+    let bob = graph.resolve_key(&"bob".into()).unwrap();
+    let likes = graph.resolve_relation("likes").unwrap();
+    assert!(graph.has_edge(alice, bob, likes));
+}
@@ -0,0 +1,84 @@
+use raphle_experimental::centrality::{betweenness, closeness, harmonic, Centrality};
+use raphle_experimental::node_id::NodeId;
+use raphle_experimental::relation::DEFAULT_RELATION;
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+use raphle_experimental::snapshot::Snapshot;
+
+mod common;
+
+use common::graph_of;
+
+/// A directed path from 1 through 2 to 3.
+fn path() -> Snapshot {
+    let (graph, rel) = graph_of(&[(1, 2), (2, 3)]);
+    Snapshot::of(&graph, rel)
+}
+
+/// An undirected star with 1 at the center and leaves 2 to 5.
+fn star() -> Snapshot {
+    let graph = RwLockedGraph::new(16).undirected();
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    for leaf in 2..=5 {
+        graph.add_edge(1, leaf, rel);
+    }
+    Snapshot::of(&graph, rel)
+}
+
+fn score(centrality: &Centrality, nid: NodeId) -> f64 {
+    centrality.ranking.score(nid).unwrap().0
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn scores_betweenness_as_networkx_does() {
+    let scores = betweenness(&path(), None, 0);
+    assert_close(score(&scores, 1), 0.0);
+    assert_close(score(&scores, 2), 0.5);
+    assert_close(score(&scores, 3), 0.0);
+
+    let scores = betweenness(&star(), None, 0);
+    assert_eq!(scores.samples, 5);
+    assert_close(score(&scores, 1), 1.0);
+    assert_close(score(&scores, 2), 0.0);
+}
+
+#[test]
+fn scores_closeness_from_incoming_distances() {
+    let scores = closeness(&path(), None, 0);
+    assert_close(score(&scores, 1), 0.0);
+    assert_close(score(&scores, 2), 0.5);
+    assert_close(score(&scores, 3), 2.0 / 3.0);
+
+    let scores = closeness(&star(), None, 0);
+    assert_close(score(&scores, 1), 1.0);
+    assert_close(score(&scores, 5), 4.0 / 7.0);
+}
+
+#[test]
+fn scores_harmonic_centrality() {
+    let scores = harmonic(&star(), None, 0);
+    assert_close(score(&scores, 1), 4.0);
+    assert_close(score(&scores, 3), 2.5);
+    assert_eq!(scores.ranking.top(1)[0].0, 1);
+}
+
+#[test]
+fn samples_sources_reproducibly() {
+    let snapshot = star();
+
+    let first = harmonic(&snapshot, Some(2), 9);
+    let second = harmonic(&snapshot, Some(2), 9);
+    assert_eq!(first.samples, 2);
+    assert_eq!(first.ranking.top(5), second.ranking.top(5));
+
+    // asking for more samples than nodes measures from every node
+    assert_eq!(harmonic(&snapshot, Some(50), 9).samples, 5);
+}
@@ -0,0 +1,180 @@
+use std::thread::sleep;
+use std::time::Duration;
+
+use raphle_experimental::change_log::{self, ChangeLog};
+use raphle_experimental::relation::DEFAULT_RELATION;
+use raphle_experimental::rwlocked_graph::{Direction, GraphAction, RwLockedGraph};
+
+mod common;
+
+use common::TempDb;
+
+/// Waits for the clock to reach the next second, since the change log keeps whole seconds.
+fn next_second() -> u64 {
+    let start = change_log::now();
+    while change_log::now() == start {
+        sleep(Duration::from_millis(20));
+    }
+    change_log::now()
+}
+
+#[test]
+fn tracks_unflushed_nodes_by_offset() {
+    let mut log = ChangeLog::new();
+    log.mark_base(9);
+    log.record(GraphAction::AddEdge, 1, 2, 0, false);
+    assert_eq!(
+        log.unflushed_nodes().iter().collect::<Vec<_>>(),
+        vec![1, 2, 9]
+    );
+
+    log.mark_flushed(log.len());
+    log.record(GraphAction::RemoveEdge, 3, 2, 0, true);
+    assert_eq!(log.unflushed_nodes().iter().collect::<Vec<_>>(), vec![2, 3]);
+    assert_eq!(log.entries_from(1).len(), 1);
+    assert_eq!(log.len(), 2);
+}
+
+#[test]
+fn drops_flushed_changes_past_the_retention() {
+    let mut log = ChangeLog::with_retention(0);
+    log.record(GraphAction::AddEdge, 1, 2, 0, false);
+    let first = log.entries_from(0)[0].time;
+    next_second();
+    log.record(GraphAction::AddEdge, 1, 3, 0, false);
+
+    // only flushed changes are dropped
+    log.mark_flushed(1);
+    assert_eq!(log.len(), 2);
+    assert_eq!(log.entries_from(0).len(), 1);
+    assert_eq!(log.entries_from(1)[0].target, 3);
+    assert!(log.covers(first));
+    assert!(!log.covers(first - 1));
+    assert!(log.unflushed_nodes().contains(3));
+}
+
+#[test]
+fn keeps_flushed_changes_within_the_retention() {
+    let mut log = ChangeLog::new();
+    log.record(GraphAction::AddEdge, 1, 2, 0, false);
+    log.mark_flushed(log.len());
+
+    assert_eq!(log.entries_from(0).len(), 1);
+    assert!(log.covers(0));
+}
+
+#[test]
+fn lists_changes_after_a_time_oldest_first() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 2, rel);
+    let before = next_second() - 1;
+    graph.add_edge(1, 3, rel);
+    graph.remove_edge(1, 2, rel);
+
+    let changes = graph.get_changes_after(before);
+    let changes: Vec<_> = changes
+        .iter()
+        .map(|change| (change.action, change.target, change.existed))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (GraphAction::AddEdge, 3, false),
+            (GraphAction::RemoveEdge, 2, true)
+        ]
+    );
+}
+
+#[test]
+fn replays_the_graph_to_an_earlier_time() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 2, rel);
+    graph.add_edge(2, 3, rel);
+    let before = next_second() - 1;
+    graph.add_edge(1, 3, rel);
+    graph.remove_edge(1, 2, rel);
+    // re-adding an edge that exists is not undone as a new edge
+    graph.add_edge(2, 3, rel);
+
+    let past = graph.replay_until(before).unwrap();
+    assert!(past.has_edge(1, 2, rel));
+    assert!(past.has_edge(2, 3, rel));
+    assert!(!past.has_edge(1, 3, rel));
+    assert_eq!(past.degree(1, rel, Direction::Outgoing), 1);
+
+    // the graph itself is left as it is
+    assert!(graph.has_edge(1, 3, rel));
+    assert!(!graph.has_edge(1, 2, rel));
+}
+
+#[test]
+fn refuses_to_replay_past_dropped_changes() {
+    let db = TempDb::new("change-log-retention");
+    let graph = RwLockedGraph::new(16)
+        .with_db_path(&db.0)
+        .with_change_retention(0);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 2, rel);
+    let before = next_second() - 1;
+    graph.add_edge(1, 3, rel);
+    next_second();
+    graph.flush_updates().unwrap();
+
+    assert!(graph.replay_until(before - 1).is_none());
+    assert!(graph.replay_until(before + 1).is_some());
+    assert!(graph.get_changes_after(0).is_empty());
+    assert_eq!(graph.change_offset(), 2);
+}
+
+#[test]
+fn replays_while_edges_are_written() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    let start = change_log::now();
+
+    // replays and writes take the nodes and the change log in the same order
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            for target in 0..2_000 {
+                graph.add_edge(1, target, rel);
+            }
+        });
+        for _ in 0..200 {
+            assert!(graph.replay_until(start).is_some());
+        }
+    });
+    assert_eq!(graph.degree(1, rel, Direction::Outgoing), 2_000);
+}
+
+#[test]
+fn filters_neighbors_by_edge_timestamp() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    for (target, timestamp) in [(2, 100), (3, 200), (4, 300)] {
+        graph.add_edge(1, target, rel);
+        graph.set_edge_timestamp(1, target, rel, timestamp);
+    }
+    // edges without a timestamp never fall within a window
+    graph.add_edge(1, 5, rel);
+
+    let mut outgoing = graph.get_outgoing_edges(1, rel);
+    graph.retain_edges_within(1, &mut outgoing, rel, Direction::Outgoing, Some(150), None);
+    assert_eq!(outgoing.iter().collect::<Vec<_>>(), vec![3, 4]);
+
+    let mut incoming = graph.get_incoming_edges(3, rel);
+    graph.retain_edges_within(3, &mut incoming, rel, Direction::Incoming, None, Some(199));
+    assert!(incoming.is_empty());
+
+    let mut outgoing = graph.get_outgoing_edges(1, rel);
+    graph.retain_edges_within(
+        1,
+        &mut outgoing,
+        rel,
+        Direction::Outgoing,
+        Some(100),
+        Some(200),
+    );
+    assert_eq!(outgoing.iter().collect::<Vec<_>>(), vec![2, 3]);
+}
@@ -0,0 +1,50 @@
+use raphle_experimental::clustering::{clustering, node_clustering};
+use raphle_experimental::relation::RelationId;
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+use raphle_experimental::snapshot::Snapshot;
+
+mod common;
+
+use common::graph_of;
+
+/// A triangle of 1, 2 and 3 with a reciprocal edge and a self-loop, and 4 hanging off 3.
+fn triangle() -> (RwLockedGraph, RelationId) {
+    graph_of(&[(1, 2), (2, 3), (3, 1), (1, 3), (2, 2), (3, 4)])
+}
+
+#[test]
+fn counts_triangles_through_a_node() {
+    let (graph, rel) = triangle();
+
+    let hub = node_clustering(&graph, 3, rel);
+    assert_eq!((hub.degree, hub.triangles), (3, 1));
+    assert!((hub.coefficient - 1.0 / 3.0).abs() < 1e-9);
+
+    let corner = node_clustering(&graph, 2, rel);
+    assert_eq!((corner.degree, corner.triangles), (2, 1));
+    assert_eq!(corner.coefficient, 1.0);
+
+    let leaf = node_clustering(&graph, 4, rel);
+    assert_eq!((leaf.degree, leaf.triangles, leaf.coefficient), (1, 0, 0.0));
+}
+
+#[test]
+fn counts_triangles_of_the_whole_graph() {
+    let (graph, rel) = triangle();
+    let stats = clustering(&Snapshot::of(&graph, rel));
+
+    assert_eq!(stats.triangles, 1);
+    // three of the five paths of two edges are closed
+    assert!((stats.transitivity - 0.6).abs() < 1e-9);
+    assert!((stats.average_coefficient - 7.0 / 12.0).abs() < 1e-9);
+}
+
+#[test]
+fn finds_no_triangles_in_a_star() {
+    let (graph, rel) = graph_of(&[(1, 2), (1, 3), (1, 4), (1, 5)]);
+
+    let stats = clustering(&Snapshot::of(&graph, rel));
+    assert_eq!(stats.triangles, 0);
+    assert_eq!(stats.transitivity, 0.0);
+    assert_eq!(node_clustering(&graph, 1, rel).coefficient, 0.0);
+}
@@ -0,0 +1,46 @@
+//! Helpers shared by the integration tests. Each test binary uses only some of them.
+#![allow(dead_code)]
+
+use std::io::Write;
+
+use raphle_experimental::node_id::NodeId;
+use raphle_experimental::relation::{RelationId, DEFAULT_RELATION};
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+
+/// Builds a directed graph of the given edges under the default relation.
+pub fn graph_of(edges: &[(NodeId, NodeId)]) -> (RwLockedGraph, RelationId) {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    for &(source, target) in edges {
+        graph.add_edge(source, target, rel);
+    }
+    (graph, rel)
+}
+
+/// Database file of its own for a test, removed along with its WAL files on drop.
+pub struct TempDb(pub String);
+
+impl TempDb {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("raphle-{}-{}.db", name, std::process::id()));
+        TempDb(path.to_str().unwrap().to_string())
+    }
+}
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", self.0, suffix));
+        }
+    }
+}
+
+/// Writes an input file to a path of its own and returns the path.
+pub fn input_file(name: &str, contents: &str) -> String {
+    let path = std::env::temp_dir().join(format!("raphle-{}-{}.csv", name, std::process::id()));
+    std::fs::File::create(&path)
+        .unwrap()
+        .write_all(contents.as_bytes())
+        .unwrap();
+    path.to_str().unwrap().to_string()
+}
@@ -0,0 +1,65 @@
+use raphle_experimental::community::{communities, CommunityAlgorithm};
+use raphle_experimental::node_id::NodeSet;
+use raphle_experimental::relation::DEFAULT_RELATION;
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+use raphle_experimental::snapshot::Snapshot;
+
+/// Two cliques, of 1 to 4 and of 5 to 8, joined by an edge between 4 and 5.
+fn bridged_cliques() -> Snapshot {
+    let graph = RwLockedGraph::new(16).undirected();
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    for clique in [[1, 2, 3, 4], [5, 6, 7, 8]] {
+        for (i, &a) in clique.iter().enumerate() {
+            for &b in &clique[i + 1..] {
+                graph.add_edge(a, b, rel);
+            }
+        }
+    }
+    graph.add_edge(4, 5, rel);
+    Snapshot::of(&graph, rel)
+}
+
+#[test]
+fn splits_bridged_cliques_with_louvain() {
+    let found = communities(&bridged_cliques(), CommunityAlgorithm::Louvain, 7);
+
+    assert_eq!(found.partition.count(), 2);
+    assert_eq!(found.partition.members(0), NodeSet::from_iter([1, 2, 3, 4]));
+    assert_eq!(found.partition.members(1), NodeSet::from_iter([5, 6, 7, 8]));
+    // each clique holds 6 of the 13 edges and half of the degree
+    assert!((found.modularity - (12.0 / 13.0 - 0.5)).abs() < 1e-9);
+}
+
+#[test]
+fn merges_the_ends_of_a_single_edge() {
+    let graph = RwLockedGraph::new(16).undirected();
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 2, rel);
+
+    let found = communities(&Snapshot::of(&graph, rel), CommunityAlgorithm::Louvain, 7);
+    assert_eq!(found.partition.count(), 1);
+    assert!(found.modularity.abs() < 1e-9);
+}
+
+#[test]
+fn reproduces_label_propagation_for_a_seed() {
+    let snapshot = bridged_cliques();
+    let first = communities(&snapshot, CommunityAlgorithm::LabelPropagation, 42);
+    let second = communities(&snapshot, CommunityAlgorithm::LabelPropagation, 42);
+
+    assert!(first.modularity > 0.0);
+    assert_eq!(first.modularity, second.modularity);
+    for nid in 1..=8 {
+        assert_eq!(
+            first.partition.component_of(nid),
+            second.partition.component_of(nid)
+        );
+    }
+    // every clique ends up in a single community
+    for clique in [[1, 2, 3], [6, 7, 8]] {
+        let community = first.partition.component_of(clique[0]);
+        assert!(clique
+            .iter()
+            .all(|&nid| first.partition.component_of(nid) == community));
+    }
+}
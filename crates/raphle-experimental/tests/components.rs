@@ -0,0 +1,59 @@
+use raphle_experimental::components::{strongly_connected, weakly_connected};
+use raphle_experimental::node_id::{NodeId, NodeSet};
+use raphle_experimental::relation::DEFAULT_RELATION;
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+use raphle_experimental::snapshot::Snapshot;
+
+mod common;
+
+use common::graph_of;
+
+/// A cycle of 1, 2 and 3 leading into a cycle of 4 and 5, and a separate edge from 6 to 7.
+const EDGES: [(NodeId, NodeId); 7] = [(1, 2), (2, 3), (3, 1), (3, 4), (4, 5), (5, 4), (6, 7)];
+
+#[test]
+fn links_nodes_in_either_direction() {
+    let (graph, rel) = graph_of(&EDGES);
+    let components = weakly_connected(&Snapshot::of(&graph, rel));
+
+    assert_eq!(components.count(), 2);
+    assert_eq!(components.component_of(5), Some(0));
+    assert_eq!(components.component_of(7), Some(1));
+    assert_eq!(components.component_of(9), None);
+    assert_eq!(components.members(1), NodeSet::from_iter([6, 7]));
+    assert_eq!(components.size(0), 5);
+    assert_eq!(
+        components.size_histogram(),
+        [(2, 1), (5, 1)].into_iter().collect()
+    );
+}
+
+#[test]
+fn splits_nodes_that_cannot_reach_each_other() {
+    let (graph, rel) = graph_of(&EDGES);
+    let components = strongly_connected(&Snapshot::of(&graph, rel));
+
+    assert_eq!(components.count(), 4);
+    assert_eq!(components.members(0), NodeSet::from_iter([1, 2, 3]));
+    assert_eq!(components.members(1), NodeSet::from_iter([4, 5]));
+    assert_eq!(components.component_of(6), Some(2));
+    assert_eq!(components.component_of(7), Some(3));
+    assert_eq!(
+        components.size_histogram(),
+        [(1, 2), (2, 1), (3, 1)].into_iter().collect()
+    );
+}
+
+#[test]
+fn follows_long_paths_without_recursing() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    for node in 0..100_000 {
+        graph.add_edge(node, node + 1, rel);
+    }
+    graph.add_edge(100_000, 0, rel);
+
+    let components = strongly_connected(&Snapshot::of(&graph, rel));
+    assert_eq!(components.count(), 1);
+    assert_eq!(components.size(0), 100_001);
+}
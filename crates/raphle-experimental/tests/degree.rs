@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+
+use raphle_experimental::change_log;
+use raphle_experimental::degree_index::DegreeIndex;
+use raphle_experimental::relation::DEFAULT_RELATION;
+use raphle_experimental::rwlocked_graph::{Direction, RwLockedGraph};
+
+fn histogram(counts: &[(u64, u64)]) -> BTreeMap<u64, u64> {
+    counts.iter().copied().collect()
+}
+
+#[test]
+fn moves_nodes_between_buckets() {
+    let mut index = DegreeIndex::new();
+    index.update(1, 0, Direction::Outgoing, 0, 2);
+    index.update(2, 0, Direction::Outgoing, 0, 2);
+    index.update(3, 0, Direction::Outgoing, 0, 1);
+    index.update(2, 0, Direction::Outgoing, 2, 3);
+    index.update(3, 0, Direction::Outgoing, 1, 0);
+
+    assert_eq!(index.top(0, Direction::Outgoing, 10), vec![(2, 3), (1, 2)]);
+    assert_eq!(
+        index.histogram(0, Direction::Outgoing),
+        histogram(&[(2, 1), (3, 1)])
+    );
+    assert!(index.top(0, Direction::Incoming, 10).is_empty());
+}
+
+#[test]
+fn follows_added_and_removed_edges() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    let likes = graph.intern_relation("likes");
+    for (source, target) in [(1, 2), (1, 3), (1, 4), (2, 3), (4, 3)] {
+        graph.add_edge(source, target, rel);
+    }
+    // re-adding an edge and edges of other relations leave the degrees alone
+    graph.add_edge(1, 2, rel);
+    graph.add_edge(2, 1, likes);
+
+    assert_eq!(
+        graph.top_by_degree(rel, Direction::Outgoing, 2),
+        vec![(1, 3), (2, 1)]
+    );
+    assert_eq!(
+        graph.top_by_degree(rel, Direction::Incoming, 1),
+        vec![(3, 3)]
+    );
+    assert_eq!(
+        graph.degree_histogram(rel, Direction::Outgoing),
+        histogram(&[(1, 2), (3, 1)])
+    );
+
+    graph.remove_edge(1, 3, rel);
+    graph.remove_edge(1, 3, rel);
+    assert_eq!(
+        graph.top_by_degree(rel, Direction::Incoming, 3),
+        vec![(3, 2), (2, 1), (4, 1)]
+    );
+
+    graph.remove_node(1);
+    assert_eq!(
+        graph.degree_histogram(rel, Direction::Outgoing),
+        histogram(&[(1, 2)])
+    );
+    assert_eq!(
+        graph.top_by_degree(rel, Direction::Incoming, 3),
+        vec![(3, 2)]
+    );
+}
+
+#[test]
+fn counts_expired_edges_until_reaped() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 2, rel);
+    graph.add_edge(1, 3, rel);
+    let now = change_log::now();
+    graph.set_edge_expiry(1, 2, rel, now - 1);
+
+    assert_eq!(
+        graph.top_by_degree(rel, Direction::Outgoing, 1),
+        vec![(1, 2)]
+    );
+    graph.reap_expired(now);
+    assert_eq!(
+        graph.top_by_degree(rel, Direction::Outgoing, 1),
+        vec![(1, 1)]
+    );
+    assert_eq!(
+        graph.degree_histogram(rel, Direction::Incoming),
+        histogram(&[(1, 1)])
+    );
+}
+
+#[test]
+fn indexes_undirected_edges_once_per_node() {
+    let graph = RwLockedGraph::new(16).undirected();
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 2, rel);
+    graph.add_edge(3, 1, rel);
+    graph.add_edge(2, 1, rel);
+
+    assert_eq!(
+        graph.top_by_degree(rel, Direction::Incoming, 3),
+        vec![(1, 2), (2, 1), (3, 1)]
+    );
+
+    graph.remove_edge(2, 1, rel);
+    assert_eq!(
+        graph.degree_histogram(rel, Direction::Outgoing),
+        histogram(&[(1, 2)])
+    );
+}
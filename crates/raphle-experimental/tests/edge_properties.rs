@@ -0,0 +1,91 @@
+use raphle_experimental::edge_properties::EdgePropertyStore;
+use raphle_experimental::node_data::PropertyValue;
+use raphle_experimental::relation::DEFAULT_RELATION;
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+
+mod common;
+
+use common::input_file;
+
+#[test]
+fn stores_properties_by_column() {
+    let mut store = EdgePropertyStore::new();
+    store.set((1, 2, 0), "weight", PropertyValue::Float(0.5));
+    store.set((1, 2, 0), "source_app", PropertyValue::from("bsky.app"));
+    store.set((1, 3, 0), "weight", PropertyValue::Float(2.0));
+
+    assert_eq!(
+        store.get(&(1, 2, 0), "weight"),
+        Some(&PropertyValue::Float(0.5))
+    );
+    assert_eq!(store.get(&(1, 3, 0), "source_app"), None);
+    assert_eq!(
+        store.get_all(&(1, 2, 0)).keys().collect::<Vec<_>>(),
+        vec!["source_app", "weight"]
+    );
+
+    store.remove_all(&(1, 2, 0));
+    assert!(store.get_all(&(1, 2, 0)).is_empty());
+    assert!(!store.is_empty());
+}
+
+#[test]
+fn keeps_properties_per_relation() {
+    let graph = RwLockedGraph::new(16);
+    let follows = graph.intern_relation("follows");
+    let likes = graph.intern_relation("likes");
+    graph.add_edge(1, 2, follows);
+    graph.add_edge(1, 2, likes);
+    graph.set_edge_property(1, 2, follows, "weight", PropertyValue::Int(1));
+    graph.set_edge_property(1, 2, likes, "weight", PropertyValue::Int(5));
+
+    assert_eq!(
+        graph.get_edge_property(1, 2, follows, "weight"),
+        Some(PropertyValue::Int(1))
+    );
+    assert_eq!(
+        graph.get_edge_property(1, 2, likes, "weight"),
+        Some(PropertyValue::Int(5))
+    );
+}
+
+#[test]
+fn drops_properties_of_removed_edges() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 2, rel);
+    graph.set_edge_property(1, 2, rel, "weight", PropertyValue::Int(3));
+
+    graph.remove_edge(1, 2, rel);
+    graph.add_edge(1, 2, rel);
+
+    assert!(graph.get_edge_properties(1, 2, rel).is_empty());
+}
+
+#[test]
+fn loads_property_columns_from_csv() {
+    let path = input_file("edge-props", "1 2 1700000000 0.5\n1 3  2.5\n2 3\n");
+
+    let mut graph = RwLockedGraph::new(16);
+    graph
+        .load_from_csv(
+            &path,
+            Some(b' '),
+            DEFAULT_RELATION,
+            &["created_at", "weight"],
+        )
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let rel = graph.resolve_relation(DEFAULT_RELATION).unwrap();
+    let properties = graph.get_edge_properties(1, 2, rel);
+    assert_eq!(properties["created_at"], PropertyValue::Int(1_700_000_000));
+    assert_eq!(properties["weight"], PropertyValue::Float(0.5));
+    // empty and missing fields leave the property unset
+    assert_eq!(graph.get_edge_property(1, 3, rel, "created_at"), None);
+    assert_eq!(
+        graph.get_edge_property(1, 3, rel, "weight"),
+        Some(PropertyValue::Float(2.5))
+    );
+    assert!(graph.get_edge_properties(2, 3, rel).is_empty());
+}
@@ -0,0 +1,109 @@
+use raphle_experimental::change_log;
+use raphle_experimental::expiry::ExpiryIndex;
+use raphle_experimental::neighbor_list::{NeighborFilter, Selection};
+use raphle_experimental::node_id::NodeSet;
+use raphle_experimental::relation::DEFAULT_RELATION;
+use raphle_experimental::rwlocked_graph::{Direction, GraphAction, RwLockedGraph};
+
+const ALL: Selection = Selection::Page {
+    after: None,
+    limit: 100,
+    reverse: false,
+};
+
+#[test]
+fn orders_expired_edges_by_expiry() {
+    let mut index = ExpiryIndex::new();
+    index.set((1, 2, 0), 30);
+    index.set((1, 3, 0), 10);
+    index.set((2, 3, 0), 20);
+    index.set((2, 3, 0), 40);
+
+    assert_eq!(index.expired(30), vec![(1, 3, 0), (1, 2, 0)]);
+    assert_eq!(
+        index.expired_neighbors(1, 0, Direction::Outgoing, 10),
+        vec![3]
+    );
+    assert!(index
+        .expired_neighbors(3, 0, Direction::Incoming, 30)
+        .contains(&1));
+
+    index.clear(&(1, 2, 0));
+    assert_eq!(index.expires_at(&(1, 2, 0)), None);
+    assert_eq!(index.expired(100), vec![(1, 3, 0), (2, 3, 0)]);
+}
+
+#[test]
+fn hides_expired_edges_from_reads() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 2, rel);
+    graph.add_edge(1, 3, rel);
+    graph.add_edge(1, 4, rel);
+    let now = change_log::now();
+    graph.set_edge_expiry(1, 2, rel, now - 1);
+    graph.set_edge_expiry(1, 3, rel, now + 3600);
+
+    assert!(!graph.has_edge(1, 2, rel));
+    assert!(graph.has_edge(1, 3, rel));
+    assert_eq!(
+        graph.get_outgoing_edges(1, rel).iter().collect::<Vec<_>>(),
+        vec![3, 4]
+    );
+    assert!(graph.get_incoming_edges(2, rel).is_empty());
+    assert_eq!(
+        graph.union_neighbors(&NodeSet::from_iter([1]), rel, Direction::Outgoing),
+        NodeSet::from_iter([3, 4])
+    );
+    assert_eq!(
+        graph
+            .read_adjacency()
+            .neighbors(1, rel, Direction::Outgoing),
+        NodeSet::from_iter([3, 4])
+    );
+    let listed = graph.list_neighbors(
+        1,
+        rel,
+        Direction::Outgoing,
+        &NeighborFilter::default(),
+        &ALL,
+    );
+    assert_eq!(listed.nodes, vec![3, 4]);
+}
+
+#[test]
+fn reaps_expired_edges_as_logged_removals() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 2, rel);
+    graph.add_edge(1, 3, rel);
+    let now = change_log::now();
+    graph.set_edge_expiry(1, 2, rel, now - 1);
+    graph.set_edge_expiry(1, 3, rel, now + 3600);
+    let offset = graph.change_offset();
+
+    assert_eq!(graph.reap_expired(now), 1);
+    assert_eq!(graph.reap_expired(now), 0);
+
+    let changes = graph.get_changes_after(0);
+    let reaped = &changes[offset..];
+    assert_eq!(reaped.len(), 1);
+    assert_eq!(reaped[0].action, GraphAction::RemoveEdge);
+    assert_eq!((reaped[0].source, reaped[0].target), (1, 2));
+    assert_eq!(graph.degree(1, rel, Direction::Outgoing), 1);
+    assert_eq!(graph.reap_expired(now + 3600), 1);
+    assert!(graph.get_outgoing_edges(1, rel).is_empty());
+}
+
+#[test]
+fn makes_re_added_edges_permanent() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 2, rel);
+    graph.set_edge_expiry(1, 2, rel, change_log::now() - 1);
+
+    graph.add_edge(1, 2, rel);
+
+    assert!(graph.has_edge(1, 2, rel));
+    assert_eq!(graph.reap_expired(change_log::now()), 0);
+}
@@ -0,0 +1,122 @@
+use std::io::ErrorKind;
+
+use raphle_experimental::id_map::{IdMap, NodeKey};
+use raphle_experimental::relation::DEFAULT_RELATION;
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+
+mod common;
+
+use common::{input_file, TempDb};
+
+#[test]
+fn assigns_dense_ids_in_order_of_first_sight() {
+    let mut id_map = IdMap::new(4);
+    let did = NodeKey::from("did:plc:abc");
+    let id = NodeKey::from(9_000_000_000);
+
+    assert_eq!(id_map.get_or_insert(&did), 0);
+    assert_eq!(id_map.get_or_insert(&id), 1);
+    assert_eq!(id_map.get_or_insert(&did), 0);
+    assert_eq!(id_map.len(), 2);
+    assert_eq!(id_map.key(1), Some(&id));
+    assert_eq!(id_map.get(&NodeKey::from("unseen")), None);
+}
+
+#[test]
+fn round_trips_external_keys_through_the_graph() {
+    let graph = RwLockedGraph::with_id_mapping(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    let alice = graph
+        .intern_key(&NodeKey::from("alice.bsky.social"))
+        .unwrap();
+    let bob = graph.intern_key(&NodeKey::from(42)).unwrap();
+    graph.add_edge(alice, bob, rel);
+
+    assert_eq!(
+        graph.resolve_key(&NodeKey::from("alice.bsky.social")),
+        Some(alice)
+    );
+    assert_eq!(
+        graph.external_key(alice),
+        NodeKey::Str("alice.bsky.social".to_string())
+    );
+    assert_eq!(graph.external_key(bob), NodeKey::Int(42));
+    assert!(graph.has_edge(alice, bob, rel));
+    assert_eq!(graph.resolve_key(&NodeKey::from("carol")), None);
+}
+
+#[test]
+fn uses_integer_keys_as_ids_without_mapping() {
+    let graph = RwLockedGraph::new(16);
+    assert_eq!(graph.resolve_key(&NodeKey::from(7)), Some(7));
+    assert_eq!(graph.intern_key(&NodeKey::from("alice")), None);
+    assert_eq!(graph.external_key(7), NodeKey::Int(7));
+}
+
+#[test]
+fn loads_string_keys_from_csv() {
+    let path = input_file("id-map-load", "alice bob\nbob carol\nalice carol\n");
+    let mut graph = RwLockedGraph::with_id_mapping(16);
+    graph
+        .load_from_csv(&path, Some(b' '), DEFAULT_RELATION, &[])
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let rel = graph.resolve_relation(DEFAULT_RELATION).unwrap();
+    let key = |raw: &str| graph.resolve_key(&NodeKey::from(raw)).unwrap();
+    assert_eq!(key("alice"), 0);
+    assert!(graph.has_edge(key("alice"), key("bob"), rel));
+    assert!(graph.has_edge(key("bob"), key("carol"), rel));
+    assert_eq!(graph.get_outgoing_edges(key("alice"), rel).len(), 2);
+}
+
+#[test]
+fn rejects_missing_and_empty_keys_as_invalid_data() {
+    for (name, contents) in [("missing", "alice bob\ncarol\n"), ("empty", "alice  bob\n")] {
+        let path = input_file(&format!("id-map-{}", name), contents);
+        let mut graph = RwLockedGraph::with_id_mapping(16);
+        let error = graph
+            .load_from_csv(&path, Some(b' '), DEFAULT_RELATION, &[])
+            .unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(error.kind(), ErrorKind::InvalidData, "{} key", name);
+        assert!(!*graph.is_loaded.read().unwrap());
+    }
+}
+
+#[test]
+fn leaves_rejected_keys_out_of_errors() {
+    let path = input_file("id-map-unmapped", "1 2\nsecret-handle 3\n");
+    let mut graph = RwLockedGraph::new(16);
+    let error = graph
+        .load_from_csv(&path, Some(b' '), DEFAULT_RELATION, &[])
+        .unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert!(!error.to_string().contains("secret-handle"));
+    assert!(error.to_string().contains("row 2"));
+}
+
+#[test]
+fn persists_assigned_keys_on_flush() {
+    let db = TempDb::new("id-map");
+    let graph = RwLockedGraph::with_id_mapping(16).with_db_path(&db.0);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    let alice = graph.intern_key(&NodeKey::from("alice")).unwrap();
+    let bob = graph.intern_key(&NodeKey::from(42)).unwrap();
+    graph.add_edge(alice, bob, rel);
+    graph.flush_updates().unwrap();
+
+    let conn = rusqlite::Connection::open(&db.0).unwrap();
+    let keys: Vec<(i64, String)> = conn
+        .prepare("SELECT nid, key FROM node_keys ORDER BY nid")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(keys, vec![(0, "alice".to_string()), (1, "42".to_string())]);
+}
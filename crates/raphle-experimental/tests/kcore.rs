@@ -0,0 +1,52 @@
+use raphle_experimental::kcore::{coreness, DegreeMode};
+use raphle_experimental::node_id::{NodeId, NodeSet};
+use raphle_experimental::snapshot::Snapshot;
+
+mod common;
+
+use common::graph_of;
+
+fn snapshot_of(edges: &[(NodeId, NodeId)]) -> Snapshot {
+    let (graph, rel) = graph_of(edges);
+    Snapshot::of(&graph, rel)
+}
+
+#[test]
+fn peels_a_clique_with_a_tail() {
+    // a clique of 1 to 4 with 5 and 6 hanging off it, and a self-loop on 7
+    let snapshot = snapshot_of(&[
+        (1, 2),
+        (1, 3),
+        (1, 4),
+        (2, 3),
+        (2, 4),
+        (4, 3),
+        (3, 2),
+        (5, 1),
+        (5, 6),
+        (7, 7),
+    ]);
+    let cores = coreness(&snapshot, DegreeMode::Total);
+
+    assert_eq!(cores.max_core(), 3);
+    assert_eq!(cores.members(3), NodeSet::from_iter([1, 2, 3, 4]));
+    assert_eq!(cores.core_of(5), Some(1));
+    assert_eq!(cores.core_of(6), Some(1));
+    assert_eq!(cores.core_of(7), Some(0));
+    assert_eq!(cores.core_of(9), None);
+    assert_eq!(cores.members(1).len(), 6);
+}
+
+#[test]
+fn counts_edges_by_direction() {
+    // a cycle of 1, 2 and 3 that 4 points into
+    let snapshot = snapshot_of(&[(1, 2), (2, 3), (3, 1), (4, 1)]);
+
+    let cores = coreness(&snapshot, DegreeMode::In);
+    assert_eq!(cores.members(1), NodeSet::from_iter([1, 2, 3]));
+    assert_eq!(cores.core_of(4), Some(0));
+
+    let cores = coreness(&snapshot, DegreeMode::Out);
+    assert_eq!(cores.members(1), NodeSet::from_iter([1, 2, 3, 4]));
+    assert_eq!(cores.max_core(), 1);
+}
@@ -0,0 +1,142 @@
+use raphle_experimental::change_log;
+use raphle_experimental::neighbor_list::{NeighborFilter, NeighborList, Selection};
+use raphle_experimental::node_id::NodeId;
+use raphle_experimental::relation::RelationId;
+use raphle_experimental::rwlocked_graph::{Direction, RwLockedGraph};
+
+mod common;
+
+use common::graph_of;
+
+/// Builds a graph where 1 follows 2 to 11, and the even ones are labeled "verified".
+fn following() -> (RwLockedGraph, RelationId) {
+    let edges: Vec<_> = (2..=11).map(|target| (1, target)).collect();
+    let (graph, rel) = graph_of(&edges);
+    for target in (2..=11).step_by(2) {
+        graph.add_node_label(target, "verified");
+    }
+    (graph, rel)
+}
+
+fn page(after: Option<NodeId>, limit: usize, reverse: bool) -> Selection {
+    Selection::Page {
+        after,
+        limit,
+        reverse,
+    }
+}
+
+fn list(graph: &RwLockedGraph, rel: RelationId, selection: Selection) -> NeighborList {
+    graph.list_neighbors(
+        1,
+        rel,
+        Direction::Outgoing,
+        &NeighborFilter::default(),
+        &selection,
+    )
+}
+
+#[test]
+fn pages_through_neighbors_with_cursors() {
+    let (graph, rel) = following();
+
+    let first = list(&graph, rel, page(None, 4, false));
+    assert_eq!((first.nodes, first.next), (vec![2, 3, 4, 5], Some(5)));
+    let second = list(&graph, rel, page(Some(5), 4, false));
+    assert_eq!((second.nodes, second.next), (vec![6, 7, 8, 9], Some(9)));
+    let last = list(&graph, rel, page(Some(9), 4, false));
+    assert_eq!((last.nodes, last.next), (vec![10, 11], None));
+
+    // a page that ends with the last neighbor has no next page
+    let exact = list(&graph, rel, page(Some(7), 4, false));
+    assert_eq!((exact.nodes, exact.next), (vec![8, 9, 10, 11], None));
+    // cursors need not be neighbors themselves
+    let past = list(&graph, rel, page(Some(0), 2, false));
+    assert_eq!(past.nodes, vec![2, 3]);
+}
+
+#[test]
+fn pages_backwards_in_reverse() {
+    let (graph, rel) = following();
+
+    let first = list(&graph, rel, page(None, 4, true));
+    assert_eq!((first.nodes, first.next), (vec![11, 10, 9, 8], Some(8)));
+    let second = list(&graph, rel, page(Some(8), 4, true));
+    assert_eq!((second.nodes, second.next), (vec![7, 6, 5, 4], Some(4)));
+    let last = list(&graph, rel, page(Some(4), 4, true));
+    assert_eq!((last.nodes, last.next), (vec![3, 2], None));
+
+    let past = list(&graph, rel, page(Some(100), 2, true));
+    assert_eq!(past.nodes, vec![11, 10]);
+}
+
+#[test]
+fn pages_through_filtered_neighbors() {
+    let (graph, rel) = following();
+    let verified = NeighborFilter {
+        label: Some("verified"),
+        ..NeighborFilter::default()
+    };
+    let listed =
+        |selection| graph.list_neighbors(1, rel, Direction::Outgoing, &verified, &selection);
+
+    let first = listed(page(None, 2, false));
+    assert_eq!((first.nodes, first.next), (vec![2, 4], Some(4)));
+    let last = listed(page(Some(8), 2, false));
+    assert_eq!((last.nodes, last.next), (vec![10], None));
+
+    let incoming = graph.list_neighbors(
+        4,
+        rel,
+        Direction::Incoming,
+        &NeighborFilter::default(),
+        &page(None, 10, true),
+    );
+    assert_eq!(incoming.nodes, vec![1]);
+}
+
+#[test]
+fn samples_the_same_neighbors_for_a_seed() {
+    let (graph, rel) = following();
+    let sample = |size, seed| list(&graph, rel, Selection::Sample { size, seed });
+
+    let first = sample(4, 17);
+    assert_eq!(first.nodes.len(), 4);
+    assert_eq!(first.next, None);
+    assert!(first.nodes.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(first.nodes.iter().all(|nid| (2..=11).contains(nid)));
+    assert_eq!(sample(4, 17).nodes, first.nodes);
+
+    // a sample as large as the neighbor set holds all of it
+    assert_eq!(sample(50, 3).nodes, (2..=11).collect::<Vec<_>>());
+}
+
+#[test]
+fn samples_only_neighbors_the_filter_keeps() {
+    let (graph, rel) = following();
+    graph.set_edge_expiry(1, 2, rel, change_log::now() - 1);
+    let verified = NeighborFilter {
+        label: Some("verified"),
+        ..NeighborFilter::default()
+    };
+
+    let sampled = graph.list_neighbors(
+        1,
+        rel,
+        Direction::Outgoing,
+        &verified,
+        &Selection::Sample { size: 3, seed: 5 },
+    );
+    assert_eq!(sampled.nodes.len(), 3);
+    assert!(sampled.nodes.iter().all(|nid| [4, 6, 8, 10].contains(nid)));
+
+    // the expired edge to 2 is never sampled
+    let sampled = graph.list_neighbors(
+        1,
+        rel,
+        Direction::Outgoing,
+        &verified,
+        &Selection::Sample { size: 10, seed: 5 },
+    );
+    assert_eq!(sampled.nodes, vec![4, 6, 8, 10]);
+}
@@ -0,0 +1,75 @@
+use raphle_experimental::node_data::PropertyValue;
+use raphle_experimental::node_id::NodeSet;
+use raphle_experimental::relation::DEFAULT_RELATION;
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+
+#[test]
+fn parses_property_values_from_input_fields() {
+    assert_eq!(PropertyValue::from("42"), PropertyValue::Int(42));
+    assert_eq!(PropertyValue::from("0.5"), PropertyValue::Float(0.5));
+    assert_eq!(PropertyValue::from("true"), PropertyValue::Bool(true));
+    assert_eq!(
+        PropertyValue::from("bsky.app"),
+        PropertyValue::Str("bsky.app".to_string())
+    );
+}
+
+#[test]
+fn adds_and_removes_labels() {
+    let graph = RwLockedGraph::new(16);
+    graph.add_node_label(1, "verified");
+    graph.add_node_label(1, "bot");
+    graph.add_node_label(2, "bot");
+    graph.remove_node_label(1, "bot");
+
+    assert_eq!(graph.get_node_labels(1), vec!["verified".to_string()]);
+    assert_eq!(graph.get_node_labels(2), vec!["bot".to_string()]);
+    assert!(graph.get_node_labels(3).is_empty());
+}
+
+#[test]
+fn sets_and_removes_properties() {
+    let graph = RwLockedGraph::new(16);
+    graph.set_node_property(1, "lang", Some(PropertyValue::from("en")));
+    graph.set_node_property(1, "followers", Some(PropertyValue::Int(10)));
+    graph.set_node_property(1, "followers", Some(PropertyValue::Int(11)));
+    graph.set_node_property(1, "lang", None);
+
+    let properties = graph.get_node_properties(1);
+    assert_eq!(properties.len(), 1);
+    assert_eq!(properties["followers"], PropertyValue::Int(11));
+}
+
+#[test]
+fn narrows_neighbor_sets_to_a_label() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    for target in 2..=5 {
+        graph.add_edge(1, target, rel);
+    }
+    graph.add_node_label(3, "verified");
+    graph.add_node_label(5, "verified");
+
+    let mut neighbors = graph.get_outgoing_edges(1, rel);
+    graph.retain_labeled(&mut neighbors, "verified");
+    assert_eq!(neighbors.iter().collect::<Vec<_>>(), vec![3, 5]);
+
+    let mut neighbors = graph.get_outgoing_edges(1, rel);
+    graph.retain_labeled(&mut neighbors, "unknown");
+    assert_eq!(neighbors, NodeSet::new());
+}
+
+#[test]
+fn drops_labels_and_properties_of_removed_nodes() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 2, rel);
+    graph.add_node_label(2, "verified");
+    graph.set_node_property(2, "lang", Some(PropertyValue::from("en")));
+
+    graph.remove_node(2);
+
+    assert!(graph.get_node_labels(2).is_empty());
+    assert!(graph.get_node_properties(2).is_empty());
+    assert!(graph.get_outgoing_edges(1, rel).is_empty());
+}
@@ -0,0 +1,72 @@
+use raphle_experimental::rank::{pagerank, PageRankParams};
+use raphle_experimental::snapshot::Snapshot;
+
+mod common;
+
+use common::graph_of;
+
+fn total(scores: &[(impl Copy, f64)]) -> f64 {
+    scores.iter().map(|&(_, score)| score).sum()
+}
+
+#[test]
+fn scores_a_cycle_evenly() {
+    let (graph, rel) = graph_of(&[(1, 2), (2, 3), (3, 1)]);
+    let rank = pagerank(&Snapshot::of(&graph, rel), &PageRankParams::default(), None);
+
+    assert!(rank.converged);
+    for (_, score) in rank.ranking.top(10) {
+        assert!((score - 1.0 / 3.0).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn ranks_the_hub_of_a_star_first() {
+    // the hub has no outgoing edges, so its mass is spread like random jumps
+    let (graph, rel) = graph_of(&[(2, 1), (3, 1), (4, 1), (5, 1), (2, 3)]);
+    let rank = pagerank(&Snapshot::of(&graph, rel), &PageRankParams::default(), None);
+
+    let top = rank.ranking.top(10);
+    assert_eq!(top.len(), 5);
+    assert_eq!(top[0].0, 1);
+    assert!((total(&top) - 1.0).abs() < 1e-6);
+    let (score, position) = rank.ranking.score(1).unwrap();
+    assert_eq!((score, position), (top[0].1, 0));
+    assert_eq!(rank.ranking.score(3).unwrap().1, 1);
+    assert!(rank.ranking.score(9).is_none());
+}
+
+#[test]
+fn personalizes_scores_around_the_seeds() {
+    let (graph, rel) = graph_of(&[(1, 2), (2, 1), (3, 4), (4, 3)]);
+    let snapshot = Snapshot::of(&graph, rel);
+    let rank = pagerank(&snapshot, &PageRankParams::default(), Some(&[1]));
+
+    let top = rank.ranking.top(10);
+    assert_eq!(
+        top.iter().map(|&(nid, _)| nid).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    assert!((total(&top) - 1.0).abs() < 1e-6);
+    assert_eq!(rank.ranking.score(4).unwrap().0, 0.0);
+
+    // without a seed in the snapshot, every node can be jumped to
+    let rank = pagerank(&snapshot, &PageRankParams::default(), Some(&[99]));
+    let top = rank.ranking.top(10);
+    assert_eq!(top.len(), 4);
+    assert!((total(&top) - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn stops_at_the_iteration_limit() {
+    let (graph, rel) = graph_of(&[(1, 2), (2, 3), (3, 1), (1, 3)]);
+    let params = PageRankParams {
+        max_iterations: 2,
+        ..PageRankParams::default()
+    };
+    let rank = pagerank(&Snapshot::of(&graph, rel), &params, None);
+
+    assert_eq!(rank.iterations, 2);
+    assert!(!rank.converged);
+    assert!((total(&rank.ranking.top(10)) - 1.0).abs() < 1e-6);
+}
@@ -0,0 +1,77 @@
+use raphle_experimental::node_id::NodeId;
+use raphle_experimental::recommend::{recommend, Recommendations, ScorerKind};
+use raphle_experimental::relation::RelationId;
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+
+mod common;
+
+use common::graph_of;
+
+/// Builds a graph where 1 follows 2 and 3, 2 follows 1, 4 and 5, and 3 follows 4 and 6.
+fn friends() -> (RwLockedGraph, RelationId) {
+    graph_of(&[(1, 2), (1, 3), (2, 1), (2, 4), (2, 5), (3, 4), (3, 6)])
+}
+
+fn nodes(recommendations: &Recommendations) -> Vec<NodeId> {
+    recommendations
+        .candidates
+        .iter()
+        .map(|candidate| candidate.node)
+        .collect()
+}
+
+#[test]
+fn ranks_friends_of_friends_by_each_scorer() {
+    let (graph, rel) = friends();
+    let run = |kind: ScorerKind| recommend(&graph, 1, rel, None, kind.scorer(), 10, 100);
+
+    let common = run(ScorerKind::CommonNeighbors);
+    assert_eq!(nodes(&common), vec![4, 5, 6]);
+    assert_eq!(common.candidates[0].score, 2.0);
+    assert!(!common.truncated);
+
+    // paths through 3, which follows fewer accounts than 2, count for more
+    let adamic_adar = run(ScorerKind::AdamicAdar);
+    assert_eq!(nodes(&adamic_adar), vec![4, 6, 5]);
+    let expected = 1.0 / 4f64.ln() + 1.0 / 3f64.ln();
+    assert!((adamic_adar.candidates[0].score - expected).abs() < 1e-9);
+
+    let jaccard = run(ScorerKind::Jaccard);
+    let scores: Vec<_> = jaccard
+        .candidates
+        .iter()
+        .map(|candidate| (candidate.node, candidate.score))
+        .collect();
+    assert_eq!(scores, vec![(4, 1.0), (5, 0.5), (6, 0.5)]);
+}
+
+#[test]
+fn leaves_out_blocked_nodes() {
+    let (graph, rel) = friends();
+    let blocks = graph.intern_relation("blocks");
+    graph.add_edge(6, 1, blocks);
+
+    let recommendations = recommend(
+        &graph,
+        1,
+        rel,
+        Some(blocks),
+        ScorerKind::CommonNeighbors.scorer(),
+        10,
+        100,
+    );
+    assert_eq!(nodes(&recommendations), vec![4, 5]);
+}
+
+#[test]
+fn skips_neighbors_past_the_fan_out() {
+    let (graph, rel) = friends();
+    let scorer = ScorerKind::CommonNeighbors.scorer();
+
+    let recommendations = recommend(&graph, 1, rel, None, scorer, 10, 2);
+    assert_eq!(nodes(&recommendations), vec![4, 6]);
+    assert!(recommendations.truncated);
+
+    let recommendations = recommend(&graph, 1, rel, None, scorer, 1, 100);
+    assert_eq!(nodes(&recommendations), vec![4]);
+}
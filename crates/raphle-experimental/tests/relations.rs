@@ -0,0 +1,157 @@
+use raphle_experimental::node_id::NodeSet;
+use raphle_experimental::relation::DEFAULT_RELATION;
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+use rusqlite::Connection;
+
+mod common;
+
+use common::TempDb;
+
+fn node_rows(db: &TempDb) -> Vec<(i64, String, NodeSet)> {
+    let conn = Connection::open(&db.0).unwrap();
+    let mut stmt = conn
+        .prepare("SELECT nid, relation, outgoing FROM nodes ORDER BY nid, relation")
+        .unwrap();
+    let rows = stmt
+        .query_map([], |row| {
+            let outgoing: Vec<u8> = row.get(2)?;
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                NodeSet::deserialize_from(&outgoing[..]).unwrap(),
+            ))
+        })
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    rows
+}
+
+#[test]
+fn registers_the_default_relation_first() {
+    let graph = RwLockedGraph::new(16);
+    let follows = graph.intern_relation("follows");
+
+    assert_eq!(graph.resolve_relation(DEFAULT_RELATION), Some(0));
+    assert_eq!(graph.intern_relation("follows"), follows);
+    assert_eq!(graph.relation_name(follows).as_deref(), Some("follows"));
+    assert_eq!(graph.resolve_relation("blocks"), None);
+}
+
+#[test]
+fn keeps_relations_apart() {
+    let graph = RwLockedGraph::new(16);
+    let follows = graph.intern_relation("follows");
+    let blocks = graph.intern_relation("blocks");
+    graph.add_edge(1, 2, follows);
+    graph.add_edge(1, 2, blocks);
+    graph.add_edge(1, 3, blocks);
+
+    graph.remove_edge(1, 2, blocks);
+
+    assert!(graph.has_edge(1, 2, follows));
+    assert!(!graph.has_edge(1, 2, blocks));
+    assert_eq!(
+        graph
+            .get_outgoing_edges(1, follows)
+            .iter()
+            .collect::<Vec<_>>(),
+        vec![2]
+    );
+    assert_eq!(
+        graph
+            .get_outgoing_edges(1, blocks)
+            .iter()
+            .collect::<Vec<_>>(),
+        vec![3]
+    );
+    assert!(graph.get_incoming_edges(2, blocks).is_empty());
+}
+
+#[test]
+fn flushes_one_row_per_node_and_relation() {
+    let db = TempDb::new("relations-flush");
+    let graph = RwLockedGraph::new(16).with_db_path(&db.0);
+    let follows = graph.intern_relation("follows");
+    let blocks = graph.intern_relation("blocks");
+    graph.add_edge(1, 2, follows);
+    graph.add_edge(1, 3, blocks);
+    graph.flush_updates().unwrap();
+
+    let rows = node_rows(&db);
+    let outgoing_of_1: Vec<_> = rows
+        .iter()
+        .filter(|(nid, _, _)| *nid == 1)
+        .map(|(_, relation, outgoing)| (relation.as_str(), outgoing.iter().collect::<Vec<_>>()))
+        .collect();
+    assert_eq!(
+        outgoing_of_1,
+        vec![("blocks", vec![3]), ("follows", vec![2])]
+    );
+    assert_eq!(rows.len(), 4);
+}
+
+#[test]
+fn migrates_tables_keyed_by_node_alone() {
+    let db = TempDb::new("relations-migrate");
+    let mut legacy = vec![];
+    NodeSet::from_iter([5]).serialize_into(&mut legacy).unwrap();
+    let conn = Connection::open(&db.0).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE nodes (
+            nid INTEGER PRIMARY KEY,
+            outgoing BLOB NOT NULL,
+            incoming BLOB NOT NULL
+        )",
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO nodes (nid, outgoing, incoming) VALUES (4, ?, ?)",
+        (&legacy, &legacy),
+    )
+    .unwrap();
+    drop(conn);
+
+    let graph = RwLockedGraph::new(16).with_db_path(&db.0);
+    let follows = graph.intern_relation("follows");
+    graph.add_edge(1, 2, follows);
+    graph.flush_updates().unwrap();
+
+    let rows = node_rows(&db);
+    let legacy_rows: Vec<_> = rows.iter().filter(|(nid, _, _)| *nid == 4).collect();
+    assert_eq!(legacy_rows.len(), 1);
+    assert_eq!(legacy_rows[0].1, DEFAULT_RELATION);
+    assert_eq!(legacy_rows[0].2.iter().collect::<Vec<_>>(), vec![5]);
+    assert!(rows
+        .iter()
+        .any(|(nid, relation, _)| *nid == 1 && relation == "follows"));
+}
+
+#[test]
+fn flushes_nodes_again_after_a_failed_flush() {
+    let db = TempDb::new("relations-retry");
+    // a table that takes no rows makes every insert fail
+    let conn = Connection::open(&db.0).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE nodes (
+            nid INTEGER NOT NULL CHECK (nid < 0),
+            relation TEXT NOT NULL,
+            outgoing BLOB NOT NULL,
+            incoming BLOB NOT NULL,
+            PRIMARY KEY (nid, relation)
+        )",
+    )
+    .unwrap();
+
+    let graph = RwLockedGraph::new(16).with_db_path(&db.0);
+    let follows = graph.intern_relation("follows");
+    graph.add_edge(1, 2, follows);
+    assert!(graph.flush_updates().is_err());
+
+    conn.execute_batch("DROP TABLE nodes").unwrap();
+    drop(conn);
+    graph.flush_updates().unwrap();
+
+    let nids: Vec<i64> = node_rows(&db).iter().map(|(nid, _, _)| *nid).collect();
+    assert_eq!(nids, vec![1, 2]);
+}
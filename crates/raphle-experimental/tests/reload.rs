@@ -0,0 +1,106 @@
+use raphle_experimental::change_log;
+use raphle_experimental::id_map::NodeKey;
+use raphle_experimental::node_data::PropertyValue;
+use raphle_experimental::node_id::{self, NodeId};
+use raphle_experimental::relation::DEFAULT_RELATION;
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+use rusqlite::Connection;
+
+mod common;
+
+use common::{input_file, TempDb};
+
+fn load(graph: &mut RwLockedGraph, name: &str, contents: &str) {
+    let path = input_file(name, contents);
+    graph
+        .load_from_csv(&path, Some(b' '), DEFAULT_RELATION, &[])
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+}
+
+fn id(graph: &RwLockedGraph, key: &str) -> NodeId {
+    graph.resolve_key(&NodeKey::from(key)).unwrap()
+}
+
+#[test]
+fn replays_writes_onto_a_reloaded_graph() {
+    let mut old = RwLockedGraph::with_id_mapping(16);
+    load(&mut old, "reload-old", "alice bob\nbob carol\n");
+    let rel = old.resolve_relation(DEFAULT_RELATION).unwrap();
+    let offset = old.change_offset();
+
+    // writes made while the new version loads
+    let (alice, bob, carol) = (id(&old, "alice"), id(&old, "bob"), id(&old, "carol"));
+    old.add_edge(alice, carol, rel);
+    old.set_edge_property(alice, carol, rel, "weight", PropertyValue::Int(3));
+    old.remove_edge(bob, carol, rel);
+    old.add_node_label(bob, "verified");
+
+    let mut new = old.empty_like();
+    load(&mut new, "reload-new", "alice bob\nbob carol\ncarol dave\n");
+    old.replay_changes_onto(&new, offset);
+
+    let rel = new.resolve_relation(DEFAULT_RELATION).unwrap();
+    let (alice, bob, carol, dave) = (
+        id(&new, "alice"),
+        id(&new, "bob"),
+        id(&new, "carol"),
+        id(&new, "dave"),
+    );
+    assert!(new.has_edge(alice, bob, rel));
+    assert!(new.has_edge(alice, carol, rel));
+    assert!(!new.has_edge(bob, carol, rel));
+    assert!(new.has_edge(carol, dave, rel));
+    assert_eq!(
+        new.get_edge_property(alice, carol, rel, "weight"),
+        Some(PropertyValue::Int(3))
+    );
+    assert_eq!(new.get_node_labels(bob), vec!["verified".to_string()]);
+}
+
+#[test]
+fn keeps_expiring_edges_that_are_reloaded() {
+    let mut old = RwLockedGraph::with_id_mapping(16);
+    load(&mut old, "reload-expiry-old", "alice bob\nalice carol\n");
+    let rel = old.resolve_relation(DEFAULT_RELATION).unwrap();
+    let offset = old.change_offset();
+    let expires_at = change_log::now() + 3600;
+    let (alice, bob, carol) = (id(&old, "alice"), id(&old, "bob"), id(&old, "carol"));
+    old.set_edge_expiry(alice, bob, rel, expires_at);
+    old.set_edge_expiry(alice, carol, rel, expires_at);
+
+    // the temporary edge to carol is no longer in the input file
+    let mut new = old.empty_like();
+    load(&mut new, "reload-expiry-new", "alice bob\n");
+    old.replay_changes_onto(&new, offset);
+
+    let rel = new.resolve_relation(DEFAULT_RELATION).unwrap();
+    assert_eq!(new.reap_expired(expires_at), 1);
+    assert!(!new.has_edge(id(&new, "alice"), id(&new, "bob"), rel));
+    assert!(!new.has_edge(id(&new, "alice"), id(&new, "carol"), rel));
+}
+
+#[test]
+fn deletes_rows_of_nodes_gone_from_the_reloaded_file() {
+    let db = TempDb::new("reload-rows");
+    let mut old = RwLockedGraph::with_id_mapping(16).with_db_path(&db.0);
+    load(&mut old, "reload-rows-old", "alice bob\nbob carol\n");
+    old.flush_updates().unwrap();
+    let offset = old.change_offset();
+
+    let mut new = old.empty_like();
+    load(&mut new, "reload-rows-new", "alice bob\n");
+    old.replay_changes_onto(&new, offset);
+    new.flush_updates().unwrap();
+
+    let conn = Connection::open(&db.0).unwrap();
+    let nids: Vec<i64> = conn
+        .prepare("SELECT DISTINCT nid FROM nodes ORDER BY nid")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let kept = [id(&new, "alice"), id(&new, "bob")];
+    assert_eq!(nids, kept.map(node_id::to_sql).to_vec());
+}
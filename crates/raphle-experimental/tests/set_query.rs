@@ -0,0 +1,134 @@
+use raphle_experimental::node_id::{NodeId, NodeSet};
+use raphle_experimental::relation::RelationId;
+use raphle_experimental::rwlocked_graph::{Direction, RwLockedGraph};
+use raphle_experimental::set_query::{overlap, SetExpr};
+use raphle_experimental::traversal::TraversalDirection;
+
+mod common;
+
+use common::graph_of;
+
+/// Builds a graph where 1 follows 3, 4 and 5, 2 follows 4, 5 and 6, and 7 follows 1.
+fn follows() -> (RwLockedGraph, RelationId) {
+    graph_of(&[(1, 3), (1, 4), (1, 5), (2, 4), (2, 5), (2, 6), (7, 1)])
+}
+
+fn out(node: NodeId, relation: RelationId) -> SetExpr {
+    SetExpr::Neighbors {
+        node,
+        relation,
+        direction: TraversalDirection::Out,
+    }
+}
+
+#[test]
+fn combines_neighbor_sets() {
+    let (graph, rel) = follows();
+
+    let union = SetExpr::Union(vec![out(1, rel), out(2, rel)]);
+    assert_eq!(union.evaluate(&graph), NodeSet::from_iter([3, 4, 5, 6]));
+
+    let both = SetExpr::Intersect(vec![out(1, rel), out(2, rel)]);
+    assert_eq!(both.evaluate(&graph), NodeSet::from_iter([4, 5]));
+
+    let only_first = SetExpr::Difference(Box::new(out(1, rel)), Box::new(out(2, rel)));
+    assert_eq!(only_first.evaluate(&graph), NodeSet::from_iter([3]));
+}
+
+#[test]
+fn nests_expressions_and_fixed_sets() {
+    let (graph, rel) = follows();
+
+    let expr = SetExpr::Difference(
+        Box::new(SetExpr::Union(vec![
+            out(1, rel),
+            SetExpr::Nodes(NodeSet::from_iter([9])),
+        ])),
+        Box::new(SetExpr::Intersect(vec![
+            out(2, rel),
+            SetExpr::Nodes(NodeSet::from_iter([5, 6])),
+        ])),
+    );
+    assert_eq!(expr.evaluate(&graph), NodeSet::from_iter([3, 4, 9]));
+}
+
+#[test]
+fn follows_the_neighbor_direction() {
+    let (graph, rel) = follows();
+    let neighbors = |direction| SetExpr::Neighbors {
+        node: 1,
+        relation: rel,
+        direction,
+    };
+
+    assert_eq!(
+        neighbors(TraversalDirection::In).evaluate(&graph),
+        NodeSet::from_iter([7])
+    );
+    assert_eq!(
+        neighbors(TraversalDirection::Both).evaluate(&graph),
+        NodeSet::from_iter([3, 4, 5, 7])
+    );
+}
+
+#[test]
+fn treats_empty_operands_as_empty_sets() {
+    let (graph, rel) = follows();
+
+    assert!(SetExpr::Intersect(vec![]).evaluate(&graph).is_empty());
+    assert!(SetExpr::Union(vec![]).evaluate(&graph).is_empty());
+    let unknown = SetExpr::Intersect(vec![out(1, rel), out(42, rel)]);
+    assert!(unknown.evaluate(&graph).is_empty());
+}
+
+#[test]
+fn samples_mutuals_by_follower_count() {
+    let (graph, rel) = follows();
+    graph.add_edge(6, 5, rel);
+
+    let mutuals = overlap(
+        &graph,
+        (1, Direction::Outgoing),
+        (2, Direction::Outgoing),
+        rel,
+        1,
+    );
+    assert_eq!(mutuals.nodes, NodeSet::from_iter([4, 5]));
+    assert_eq!(mutuals.sample, vec![5]);
+
+    // ties go to the lower ID
+    graph.add_edge(6, 4, rel);
+    let mutuals = overlap(
+        &graph,
+        (1, Direction::Outgoing),
+        (2, Direction::Outgoing),
+        rel,
+        10,
+    );
+    assert_eq!(mutuals.sample, vec![4, 5]);
+}
+
+#[test]
+fn finds_followed_accounts_that_follow_a_node() {
+    let (graph, rel) = follows();
+
+    let followed_by = overlap(
+        &graph,
+        (7, Direction::Outgoing),
+        (3, Direction::Incoming),
+        rel,
+        10,
+    );
+    assert_eq!(followed_by.nodes, NodeSet::from_iter([1]));
+    assert_eq!(followed_by.sample, vec![1]);
+
+    let none = overlap(
+        &graph,
+        (2, Direction::Outgoing),
+        (3, Direction::Incoming),
+        rel,
+        10,
+    );
+    assert!(none.nodes.is_empty());
+    assert!(none.sample.is_empty());
+}
@@ -0,0 +1,133 @@
+use raphle_experimental::node_id::NodeSet;
+use raphle_experimental::relation::{RelationId, DEFAULT_RELATION};
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+use raphle_experimental::traversal::{neighborhood, shortest_paths, TraversalDirection};
+
+mod common;
+
+use common::graph_of;
+
+/// Builds a directed graph of `1 -> 2 -> 3 -> 4` with a branch of `1 -> 5 -> 6`.
+fn chain() -> (RwLockedGraph, RelationId) {
+    graph_of(&[(1, 2), (2, 3), (3, 4), (1, 5), (5, 6)])
+}
+
+#[test]
+fn collects_nodes_by_hop() {
+    let (graph, rel) = chain();
+
+    let hood = neighborhood(&graph, 1, rel, TraversalDirection::Out, 2, None);
+    assert_eq!(
+        hood.layers,
+        vec![NodeSet::from_iter([2, 5]), NodeSet::from_iter([3, 6])]
+    );
+    assert!(!hood.truncated);
+    assert_eq!(hood.nodes(), NodeSet::from_iter([2, 3, 5, 6]));
+
+    // the traversal ends early when nothing new is reached
+    let hood = neighborhood(&graph, 1, rel, TraversalDirection::Out, 10, None);
+    assert_eq!(hood.layers.len(), 3);
+}
+
+#[test]
+fn follows_the_requested_direction() {
+    let (graph, rel) = chain();
+
+    let hood = neighborhood(&graph, 3, rel, TraversalDirection::In, 3, None);
+    assert_eq!(
+        hood.layers,
+        vec![NodeSet::from_iter([2]), NodeSet::from_iter([1])]
+    );
+
+    let hood = neighborhood(&graph, 2, rel, TraversalDirection::Both, 1, None);
+    assert_eq!(hood.layers, vec![NodeSet::from_iter([1, 3])]);
+}
+
+#[test]
+fn follows_undirected_edges_either_way() {
+    let graph = RwLockedGraph::new(16).undirected();
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(2, 1, rel);
+    graph.add_edge(3, 2, rel);
+
+    let hood = neighborhood(&graph, 1, rel, TraversalDirection::In, 2, None);
+    assert_eq!(
+        hood.layers,
+        vec![NodeSet::from_iter([2]), NodeSet::from_iter([3])]
+    );
+}
+
+#[test]
+fn truncates_at_the_node_limit() {
+    let (graph, rel) = chain();
+
+    // the cut layer keeps the lowest IDs
+    let hood = neighborhood(&graph, 1, rel, TraversalDirection::Out, 3, Some(3));
+    assert_eq!(
+        hood.layers,
+        vec![NodeSet::from_iter([2, 5]), NodeSet::from_iter([3])]
+    );
+    assert!(hood.truncated);
+
+    let hood = neighborhood(&graph, 1, rel, TraversalDirection::Out, 3, Some(2));
+    assert_eq!(hood.layers, vec![NodeSet::from_iter([2, 5])]);
+    assert!(hood.truncated);
+
+    let hood = neighborhood(&graph, 1, rel, TraversalDirection::Out, 3, Some(0));
+    assert!(hood.layers.is_empty());
+    assert!(hood.truncated);
+}
+
+/// Builds a directed graph with two shortest paths from 1 to 5, `1 -> 2 -> 4 -> 5` and
+/// `1 -> 3 -> 4 -> 5`, and a longer one through 6.
+fn diamond() -> (RwLockedGraph, RelationId) {
+    graph_of(&[
+        (1, 2),
+        (1, 3),
+        (2, 4),
+        (3, 4),
+        (4, 5),
+        (1, 6),
+        (6, 7),
+        (7, 8),
+        (8, 5),
+    ])
+}
+
+#[test]
+fn finds_every_shortest_path() {
+    let (graph, rel) = diamond();
+
+    let found = shortest_paths(&graph, 1, 5, rel, 6, 10).unwrap();
+    assert_eq!(found.distance, 3);
+    let mut paths = found.paths;
+    paths.sort();
+    assert_eq!(paths, vec![vec![1, 2, 4, 5], vec![1, 3, 4, 5]]);
+
+    let found = shortest_paths(&graph, 2, 2, rel, 6, 10).unwrap();
+    assert_eq!((found.distance, found.paths), (0, vec![vec![2]]));
+}
+
+#[test]
+fn follows_outgoing_edges_only() {
+    let (graph, rel) = diamond();
+
+    assert!(shortest_paths(&graph, 5, 1, rel, 6, 10).is_none());
+    assert!(shortest_paths(&graph, 2, 3, rel, 6, 10).is_none());
+}
+
+#[test]
+fn stops_at_the_depth_and_path_limits() {
+    let (graph, rel) = diamond();
+
+    assert!(shortest_paths(&graph, 1, 5, rel, 2, 10).is_none());
+    assert_eq!(
+        shortest_paths(&graph, 1, 5, rel, 3, 1).unwrap().paths.len(),
+        1
+    );
+
+    // a zero limit still reports the distance
+    let found = shortest_paths(&graph, 1, 5, rel, 3, 0).unwrap();
+    assert_eq!(found.distance, 3);
+    assert!(found.paths.is_empty());
+}
@@ -0,0 +1,69 @@
+use raphle_experimental::node_data::PropertyValue;
+use raphle_experimental::node_id::NodeSet;
+use raphle_experimental::relation::DEFAULT_RELATION;
+use raphle_experimental::rwlocked_graph::{Direction, RwLockedGraph};
+
+mod common;
+
+use common::input_file;
+
+#[test]
+fn links_both_nodes_of_an_edge() {
+    let graph = RwLockedGraph::new(16).undirected();
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(2, 1, rel);
+    graph.add_edge(2, 3, rel);
+
+    assert!(!graph.is_directed());
+    assert!(graph.has_edge(1, 2, rel));
+    assert!(graph.has_edge(2, 1, rel));
+    assert_eq!(graph.get_neighbors(1, rel), NodeSet::from_iter([2]));
+    assert_eq!(graph.get_neighbors(2, rel), NodeSet::from_iter([1, 3]));
+    assert!(graph.get_incoming_edges(2, rel).is_empty());
+    assert_eq!(graph.degree(2, rel, Direction::Outgoing), 2);
+
+    graph.remove_edge(1, 2, rel);
+    assert!(!graph.has_edge(2, 1, rel));
+    assert_eq!(graph.get_neighbors(1, rel), NodeSet::new());
+    assert_eq!(graph.degree(2, rel, Direction::Outgoing), 1);
+}
+
+#[test]
+fn shares_edge_data_between_directions() {
+    let graph = RwLockedGraph::new(16).undirected();
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(2, 1, rel);
+    graph.set_edge_property(2, 1, rel, "weight", PropertyValue::Int(4));
+
+    assert_eq!(
+        graph.get_edge_property(1, 2, rel, "weight"),
+        Some(PropertyValue::Int(4))
+    );
+
+    // both directions log the same edge, so the second add is a re-add
+    let offset = graph.change_offset();
+    graph.add_edge(1, 2, rel);
+    let changes = graph.get_changes_after(0);
+    let change = &changes[offset];
+    assert_eq!((change.source, change.target), (1, 2));
+    assert!(change.existed);
+}
+
+#[test]
+fn loads_reversed_rows_as_one_edge() {
+    let path = input_file("undirected", "1 2\n2 1\n2 3\n");
+
+    let mut graph = RwLockedGraph::new(16).undirected();
+    graph
+        .load_from_csv(&path, Some(b' '), DEFAULT_RELATION, &[])
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let rel = graph.resolve_relation(DEFAULT_RELATION).unwrap();
+    assert_eq!(graph.degree(1, rel, Direction::Outgoing), 1);
+    assert_eq!(graph.degree(2, rel, Direction::Outgoing), 2);
+    assert_eq!(
+        graph.degree_histogram(rel, Direction::Outgoing),
+        [(1, 2), (2, 1)].into_iter().collect()
+    );
+}
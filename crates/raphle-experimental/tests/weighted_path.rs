@@ -0,0 +1,85 @@
+use std::time::{Duration, Instant};
+
+use raphle_experimental::node_data::PropertyValue;
+use raphle_experimental::relation::RelationId;
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+use raphle_experimental::weighted_path::{cheapest_path, cheapest_paths_within, SearchError};
+
+mod common;
+
+use common::graph_of;
+
+/// Builds a graph where the direct edge from 1 to 4 costs more than the detour through 2.
+fn roads() -> (RwLockedGraph, RelationId) {
+    // edges without a weight cost 1
+    let (graph, rel) = graph_of(&[(1, 2), (2, 4), (1, 3), (3, 4), (1, 4), (4, 5)]);
+    for (source, target, weight) in [(1, 2, 1.0), (2, 4, 1.5), (1, 3, 5.0), (3, 4, 1.0)] {
+        graph.set_edge_property(source, target, rel, "weight", PropertyValue::Float(weight));
+    }
+    graph.set_edge_property(1, 4, rel, "weight", PropertyValue::Int(10));
+    (graph, rel)
+}
+
+fn later() -> Instant {
+    Instant::now() + Duration::from_secs(10)
+}
+
+#[test]
+fn finds_the_cheapest_path() {
+    let (graph, rel) = roads();
+
+    let route = cheapest_path(&graph, 1, 5, rel, "weight", None, later())
+        .unwrap()
+        .unwrap();
+    assert_eq!(route.path, vec![1, 2, 4, 5]);
+    assert_eq!(route.cost, 3.5);
+
+    let unreachable = cheapest_path(&graph, 5, 1, rel, "weight", None, later()).unwrap();
+    assert!(unreachable.is_none());
+}
+
+#[test]
+fn finds_the_same_path_with_a_heuristic() {
+    let (graph, rel) = roads();
+    let heuristic = |node| if node == 5 { 0.0 } else { 1.0 };
+
+    let route = cheapest_path(&graph, 1, 5, rel, "weight", Some(&heuristic), later())
+        .unwrap()
+        .unwrap();
+    assert_eq!(route.path, vec![1, 2, 4, 5]);
+    assert_eq!(route.cost, 3.5);
+}
+
+#[test]
+fn stops_at_the_cost_budget() {
+    let (graph, rel) = roads();
+
+    let routes = cheapest_paths_within(&graph, 1, rel, "weight", 3.0, later()).unwrap();
+    let reached: Vec<_> = routes
+        .iter()
+        .map(|route| (route.target, route.cost))
+        .collect();
+    assert_eq!(reached, vec![(2, 1.0), (4, 2.5)]);
+    assert_eq!(routes[1].path, vec![1, 2, 4]);
+
+    let routes = cheapest_paths_within(&graph, 1, rel, "weight", 0.5, later()).unwrap();
+    assert!(routes.is_empty());
+}
+
+#[test]
+fn rejects_negative_weights() {
+    let (graph, rel) = roads();
+    graph.set_edge_property(2, 4, rel, "weight", PropertyValue::Float(-1.0));
+
+    let result = cheapest_path(&graph, 1, 5, rel, "weight", None, later());
+    assert_eq!(result.unwrap_err(), SearchError::InvalidWeight);
+}
+
+#[test]
+fn times_out_past_the_deadline() {
+    let (graph, rel) = roads();
+    let deadline = Instant::now() - Duration::from_millis(1);
+
+    let result = cheapest_paths_within(&graph, 1, rel, "weight", 100.0, deadline);
+    assert_eq!(result.unwrap_err(), SearchError::TimedOut);
+}
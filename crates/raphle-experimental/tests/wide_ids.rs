@@ -0,0 +1,60 @@
+#![cfg(feature = "wide-ids")]
+
+use raphle_experimental::id_map::NodeKey;
+use raphle_experimental::relation::DEFAULT_RELATION;
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+
+mod common;
+
+use common::input_file;
+
+const BIG: u64 = u32::MAX as u64 + 10;
+
+#[test]
+fn stores_edges_between_ids_above_u32_max() {
+    let graph = RwLockedGraph::new(16);
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(BIG, BIG + 1, rel);
+    graph.add_edge(BIG, 7, rel);
+
+    assert!(graph.has_edge(BIG, BIG + 1, rel));
+    assert!(!graph.has_edge(BIG + 1, BIG, rel));
+    assert_eq!(
+        graph
+            .get_outgoing_edges(BIG, rel)
+            .iter()
+            .collect::<Vec<_>>(),
+        vec![7, BIG + 1]
+    );
+    assert_eq!(
+        graph
+            .get_incoming_edges(BIG + 1, rel)
+            .iter()
+            .collect::<Vec<_>>(),
+        vec![BIG]
+    );
+}
+
+#[test]
+fn resolves_u64_keys_without_id_mapping() {
+    let graph = RwLockedGraph::new(16);
+    assert_eq!(graph.resolve_key(&NodeKey::Int(BIG)), Some(BIG));
+    assert_eq!(graph.external_key(BIG), NodeKey::Int(BIG));
+}
+
+#[test]
+fn loads_ids_above_u32_max_from_csv() {
+    let rows = format!("{} {}\n1 {}\n", BIG, u64::MAX, BIG);
+    let path = input_file("wide-ids", &rows);
+
+    let mut graph = RwLockedGraph::new(16);
+    graph
+        .load_from_csv(&path, Some(b' '), "follows", &[])
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let rel = graph.resolve_relation("follows").unwrap();
+    assert!(graph.has_edge(BIG, u64::MAX, rel));
+    assert!(graph.has_edge(1, BIG, rel));
+    assert_eq!(graph.get_incoming_edges(BIG, rel).len(), 1);
+}
@@ -1,4 +1,19 @@
+use std::{
+    collections::{hash_map::RandomState, BTreeMap, HashMap},
+    hash::{BuildHasher, Hasher},
+};
+
 use axum::{extract::Query, http::StatusCode, response::IntoResponse, Extension, Json};
+use raphle_experimental::{
+    batch::{BatchOp, Precondition},
+    change_log,
+    id_map::NodeKey,
+    neighbor_list::{NeighborFilter, Selection},
+    node_data::PropertyValue,
+    node_id::NodeId,
+    relation::{RelationId, DEFAULT_RELATION},
+    rwlocked_graph::{Direction, GraphAction, RwLockedGraph, TIMESTAMP_PROPERTY},
+};
 use serde::{Deserialize, Serialize};
 use tracing::{error, info, warn};
 
@@ -11,143 +26,701 @@ pub struct EdgeBody {
 
 #[derive(Deserialize)]
 pub struct Edge {
-    source: u32,
-    target: u32,
+    source: NodeKey,
+    target: NodeKey,
+    relation: Option<String>,
+    #[serde(default)]
+    properties: HashMap<String, PropertyValue>,
+    /// Unix timestamp of the edge, in seconds.
+    timestamp: Option<u64>,
+    /// Seconds after which the edge expires.
+    ttl: Option<u64>,
+    /// Unix time, in seconds, at which the edge expires.
+    expires_at: Option<u64>,
+}
+
+impl Edge {
+    /// Properties of the edge, including its timestamp.
+    fn property_list(&self) -> Vec<(String, PropertyValue)> {
+        let mut properties: Vec<_> = self
+            .properties
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        if let Some(timestamp) = self.timestamp {
+            let timestamp = timestamp.try_into().unwrap_or(i64::MAX);
+            properties.push((
+                TIMESTAMP_PROPERTY.to_string(),
+                PropertyValue::Int(timestamp),
+            ));
+        }
+        properties
+    }
+
+    /// Unix time, in seconds, at which the edge expires, either given or derived from its TTL.
+    fn expiry(&self) -> Option<u64> {
+        self.expires_at
+            .or(self.ttl.map(|ttl| change_log::now().saturating_add(ttl)))
+    }
+}
+
+/// Attaches the properties, timestamp and expiry of a posted [`Edge`]. Must run after the edge
+/// is added, since adding an edge makes it permanent, and not for edges that are only queued.
+fn set_edge_metadata(
+    graph: &RwLockedGraph,
+    source: NodeId,
+    target: NodeId,
+    relation: RelationId,
+    edge: &Edge,
+) {
+    for (key, value) in edge.property_list() {
+        graph.set_edge_property(source, target, relation, &key, value);
+    }
+    if let Some(expires_at) = edge.expiry() {
+        graph.set_edge_expiry(source, target, relation, expires_at);
+    }
 }
 
 /// Sends a [`Vec<Edge>`] to the [`GraphState`]. Will enqueque new edges to the [`GraphState`]
-/// if the graph is not fully loaded. Used to post many new edges to the graph.
+/// if the graph is not fully loaded, without their properties, timestamp or expiry. Used to post
+/// many new edges to the graph.
 pub async fn post_edges(
     state: Extension<GraphState>,
     Json(body): Json<EdgeBody>,
-) -> impl IntoResponse {
+) -> Result<StatusCode, Errors> {
     for new_edge in body.new_edges {
+        let (source, target, relation) = {
+            let graph = state.graph.lock().unwrap();
+            let relation = graph.intern_relation(relation_name(&new_edge.relation));
+            match (
+                graph.intern_key(&new_edge.source),
+                graph.intern_key(&new_edge.target),
+            ) {
+                (Some(source), Some(target)) => (source, target, relation),
+                _ => return Err(Errors::InvalidNode),
+            }
+        };
+
         // If the graph isn't loaded yet, enqueque the follow requests
         if !*state.graph.lock().unwrap().is_loaded.read().unwrap() {
             state
                 .graph
                 .lock()
                 .unwrap()
-                .enqueue_add_edge(new_edge.source, new_edge.target);
+                .enqueue_add_edge(source, target, relation);
             warn!("graph not fully loaded, added edge to queue");
             continue;
         }
 
-        state
-            .graph
-            .lock()
-            .unwrap()
-            .add_edge(new_edge.source, new_edge.target)
+        let graph = state.graph.lock().unwrap();
+        graph.add_edge(source, target, relation);
+        set_edge_metadata(&graph, source, target, relation, &new_edge);
     }
     info!("successfully loaded edges");
-    StatusCode::OK
+    Ok(StatusCode::OK)
 }
 
 /// Sends a new [`Edge`] to the [`GraphState`]. Will enqueue the edge to the [`GraphState`] if
-/// the graph is not fully loaded. Used to add new, single edges to the graph.
-pub async fn post_edge(state: Extension<GraphState>, body: Json<Edge>) -> impl IntoResponse {
+/// the graph is not fully loaded, without its properties, timestamp or expiry. Used to add new,
+/// single edges to the graph.
+pub async fn post_edge(
+    state: Extension<GraphState>,
+    body: Json<Edge>,
+) -> Result<StatusCode, Errors> {
+    let graph = state.graph.lock().unwrap();
+    let (Some(source), Some(target)) = (
+        graph.intern_key(&body.source),
+        graph.intern_key(&body.target),
+    ) else {
+        return Err(Errors::InvalidNode);
+    };
+    let relation = graph.intern_relation(relation_name(&body.relation));
+
     // If the graph isn't loaded yet, enqueque the follow request
-    if !*state.graph.lock().unwrap().is_loaded.read().unwrap() {
-        state
-            .graph
-            .lock()
-            .unwrap()
-            .enqueue_add_edge(body.source, body.target);
+    if !*graph.is_loaded.read().unwrap() {
+        graph.enqueue_add_edge(source, target, relation);
         warn!("graph not loaded, added edge to queue");
-        return StatusCode::OK;
+        return Ok(StatusCode::OK);
     }
 
-    state
-        .graph
-        .lock()
-        .unwrap()
-        .add_edge(body.source, body.target);
+    graph.add_edge(source, target, relation);
+    set_edge_metadata(&graph, source, target, relation, &body);
     info!("successfully added edge");
-    StatusCode::OK
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOperation {
+    AddEdge(Edge),
+    RemoveEdge {
+        source: NodeKey,
+        target: NodeKey,
+        relation: Option<String>,
+    },
+    RemoveNode {
+        node: NodeKey,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BatchPrecondition {
+    EdgeAbsent {
+        source: NodeKey,
+        target: NodeKey,
+        relation: Option<String>,
+    },
+    EdgePresent {
+        source: NodeKey,
+        target: NodeKey,
+        relation: Option<String>,
+    },
+    OutDegreeBelow {
+        node: NodeKey,
+        relation: Option<String>,
+        limit: u64,
+    },
+}
+
+#[derive(Deserialize)]
+pub struct BatchBody {
+    #[serde(default)]
+    preconditions: Vec<BatchPrecondition>,
+    ops: Vec<BatchOperation>,
+}
+
+/// Applies a list of `add_edge`, `remove_edge` and `remove_node` operations atomically. When
+/// any of the `preconditions` does not hold, none of the operations are applied and
+/// [`Errors::FailedPrecondition`] is returned, which allows compare-and-set updates such as
+/// enforcing a follow limit. Returns [`Errors::StillLoading`] when the graph is still loading.
+pub async fn post_batch(
+    state: Extension<GraphState>,
+    Json(body): Json<BatchBody>,
+) -> Result<StatusCode, Errors> {
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let mut preconditions = Vec::with_capacity(body.preconditions.len());
+
+    // Preconditions are resolved before any key of the operations is interned, so a node that
+    // is unknown to the graph has no edges
+    for (index, precondition) in body.preconditions.iter().enumerate() {
+        let resolved =
+            match precondition {
+                BatchPrecondition::EdgeAbsent {
+                    source,
+                    target,
+                    relation,
+                } => match resolve_edge(&graph, source, target, relation) {
+                    Some((source, target, relation)) => Some(Precondition::EdgeAbsent {
+                        source,
+                        target,
+                        relation,
+                    }),
+                    None => continue,
+                },
+                BatchPrecondition::EdgePresent {
+                    source,
+                    target,
+                    relation,
+                } => resolve_edge(&graph, source, target, relation).map(
+                    |(source, target, relation)| Precondition::EdgePresent {
+                        source,
+                        target,
+                        relation,
+                    },
+                ),
+                BatchPrecondition::OutDegreeBelow {
+                    node,
+                    relation,
+                    limit,
+                } => match (
+                    graph.resolve_key(node),
+                    graph.resolve_relation(relation_name(relation)),
+                ) {
+                    (Some(node), Some(relation)) => Some(Precondition::OutDegreeBelow {
+                        node,
+                        relation,
+                        limit: *limit,
+                    }),
+                    _ if *limit > 0 => continue,
+                    _ => None,
+                },
+            };
+
+        match resolved {
+            Some(precondition) => preconditions.push(precondition),
+            None => {
+                warn!("batch precondition {} does not hold", index);
+                return Err(Errors::FailedPrecondition);
+            }
+        }
+    }
+
+    // keys are only interned once the preconditions hold, so a rejected batch registers nothing
+    let accepts_keys = body.ops.iter().all(|op| match op {
+        BatchOperation::AddEdge(edge) => {
+            graph.accepts_key(&edge.source) && graph.accepts_key(&edge.target)
+        }
+        _ => true,
+    });
+    if !accepts_keys {
+        return Err(Errors::InvalidNode);
+    }
+
+    let build_ops = || {
+        let mut ops = Vec::with_capacity(body.ops.len());
+        for op in &body.ops {
+            match op {
+                BatchOperation::AddEdge(edge) => {
+                    let (Some(source), Some(target)) = (
+                        graph.intern_key(&edge.source),
+                        graph.intern_key(&edge.target),
+                    ) else {
+                        unreachable!("keys were checked before the batch was applied");
+                    };
+                    ops.push(BatchOp::AddEdge {
+                        source,
+                        target,
+                        relation: graph.intern_relation(relation_name(&edge.relation)),
+                        properties: edge.property_list(),
+                        expires_at: edge.expiry(),
+                    });
+                }
+                // removing edges or nodes the graph does not know about changes nothing
+                BatchOperation::RemoveEdge {
+                    source,
+                    target,
+                    relation,
+                } => {
+                    if let Some((source, target, relation)) =
+                        resolve_edge(&graph, source, target, relation)
+                    {
+                        ops.push(BatchOp::RemoveEdge {
+                            source,
+                            target,
+                            relation,
+                        });
+                    }
+                }
+                BatchOperation::RemoveNode { node } => {
+                    if let Some(node) = graph.resolve_key(node) {
+                        ops.push(BatchOp::RemoveNode { node });
+                    }
+                }
+            }
+        }
+        ops
+    };
+
+    if let Err(failed) = graph.apply_batch_with(&preconditions, build_ops) {
+        warn!("batch precondition {} does not hold", failed.index);
+        return Err(Errors::FailedPrecondition);
+    }
+    info!(
+        "successfully applied batch of {} operations",
+        body.ops.len()
+    );
+    Ok(StatusCode::OK)
+}
+
+/// Resolves the keys and relation of an edge without registering them.
+fn resolve_edge(
+    graph: &RwLockedGraph,
+    source: &NodeKey,
+    target: &NodeKey,
+    relation: &Option<String>,
+) -> Option<(NodeId, NodeId, RelationId)> {
+    Some((
+        graph.resolve_key(source)?,
+        graph.resolve_key(target)?,
+        graph.resolve_relation(relation_name(relation))?,
+    ))
+}
+
+/// Most neighbors listed on a single page or in a single sample.
+const MAX_PAGE_LIMIT: usize = 10_000;
+
+/// Neighbors listed on a page when only a cursor or an order is given.
+const DEFAULT_PAGE_LIMIT: usize = 100;
+
+/// Paging and sampling of a neighbor listing. Without any of these, every neighbor is listed.
+#[derive(Deserialize)]
+pub struct PageQuery {
+    /// Lists the neighbors after this one, as returned in `next` with the previous page.
+    after: Option<NodeKey>,
+    /// Largest number of neighbors on a page.
+    limit: Option<usize>,
+    /// Lists neighbors from the highest ID down.
+    #[serde(default)]
+    reverse: bool,
+    /// Lists this many neighbors picked uniformly at random instead of a page.
+    sample: Option<usize>,
+    /// Seed of the sample, which picks the same neighbors as long as they do not change. Random
+    /// if not given.
+    seed: Option<u64>,
+}
+
+impl PageQuery {
+    /// Returns which neighbors to list, or `None` to list all of them. Returns
+    /// [`Errors::InvalidQuery`] for a sample combined with paging, a limit above
+    /// [`MAX_PAGE_LIMIT`] or an unknown cursor.
+    fn selection(&self, graph: &RwLockedGraph) -> Result<Option<Selection>, Errors> {
+        if let Some(size) = self.sample {
+            if self.after.is_some() || self.limit.is_some() || self.reverse || size > MAX_PAGE_LIMIT
+            {
+                return Err(Errors::InvalidQuery);
+            }
+            let seed = self
+                .seed
+                .unwrap_or_else(|| RandomState::new().build_hasher().finish());
+            return Ok(Some(Selection::Sample { size, seed }));
+        }
+        if self.after.is_none() && self.limit.is_none() && !self.reverse {
+            return Ok(None);
+        }
+
+        let limit = self.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+        if limit > MAX_PAGE_LIMIT {
+            return Err(Errors::InvalidQuery);
+        }
+        let after = match &self.after {
+            Some(key) => Some(graph.resolve_key(key).ok_or(Errors::InvalidQuery)?),
+            None => None,
+        };
+        Ok(Some(Selection::Page {
+            after,
+            limit,
+            reverse: self.reverse,
+        }))
+    }
 }
 
 #[derive(Serialize)]
 pub struct OutgoingEdgeResponse {
-    targets: Vec<u32>,
+    targets: Vec<NodeKey>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<Vec<BTreeMap<String, PropertyValue>>>,
+    /// Cursor of the next page, if there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next: Option<NodeKey>,
 }
 
 #[derive(Deserialize)]
 pub struct OutgoingEdgeQuery {
-    source: u32,
+    source: NodeKey,
+    relation: Option<String>,
+    label: Option<String>,
+    properties: Option<bool>,
+    since: Option<u64>,
+    until: Option<u64>,
 }
 
-/// Requests the outgoing edges from a given source node-ID (of type `u32`). Returns a [`Vec<u32>`]
-/// which is the set of target node-IDs, or returns [`Errors::StillLoading`] when the graph is
-/// still loading.
+/// Requests the outgoing edges from a given source [`NodeKey`]. Returns a [`Vec<NodeKey>`]
+/// which is the set of target node keys, optionally only those carrying a `label` or whose edge
+/// timestamp lies between `since` and `until`, and along with their edge `properties`. Targets
+/// can be paged or sampled as described on [`PageQuery`]. Returns [`Errors::StillLoading`] when
+/// the graph is still loading.
 pub async fn get_outgoing_edges(
     state: Extension<GraphState>,
     Query(query): Query<OutgoingEdgeQuery>,
+    Query(page): Query<PageQuery>,
 ) -> Result<Json<OutgoingEdgeResponse>, Errors> {
     // Return Error if not loaded
     if !*state.graph.lock().unwrap().is_loaded.read().unwrap() {
         error!("graph data not yet loaded!");
         return Err(Errors::StillLoading);
     }
-
-    let source = state.graph.lock().unwrap().get_node(query.source);
-    if source.is_none() {
-        warn!("source not present");
-        return Ok(Json(OutgoingEdgeResponse { targets: vec![] }));
+    if !state.graph.lock().unwrap().is_directed() {
+        return Err(Errors::DirectionMismatch);
     }
 
+    let (source, relation) = {
+        let graph = state.graph.lock().unwrap();
+        (
+            graph
+                .resolve_key(&query.source)
+                .and_then(|nid| graph.get_node(nid)),
+            graph.resolve_relation(relation_name(&query.relation)),
+        )
+    };
+    let (Some(source), Some(relation)) = (source, relation) else {
+        warn!("source or relation not present");
+        return Ok(Json(OutgoingEdgeResponse {
+            targets: vec![],
+            properties: None,
+            next: None,
+        }));
+    };
+
     // This feels very hacky.
     // I think there is a better way to ensure we don't spam lock the graph
     // I also think we may need to free the busy_graph?
     let busy_graph = state.graph.lock().unwrap();
-    let outgoing = busy_graph.get_outgoing_edges(source.unwrap());
+    let (outgoing, next) = match page.selection(&busy_graph)? {
+        Some(selection) => {
+            let filter = NeighborFilter {
+                label: query.label.as_deref(),
+                since: query.since,
+                until: query.until,
+            };
+            let list = busy_graph.list_neighbors(
+                source,
+                relation,
+                Direction::Outgoing,
+                &filter,
+                &selection,
+            );
+            (list.nodes, list.next)
+        }
+        None => {
+            let mut outgoing = busy_graph.get_outgoing_edges(source, relation);
+            if let Some(label) = &query.label {
+                busy_graph.retain_labeled(&mut outgoing, label);
+            }
+            if query.since.is_some() || query.until.is_some() {
+                busy_graph.retain_edges_within(
+                    source,
+                    &mut outgoing,
+                    relation,
+                    Direction::Outgoing,
+                    query.since,
+                    query.until,
+                );
+            }
+            (outgoing.iter().collect(), None)
+        }
+    };
     let targets: Vec<_> = outgoing
         .iter()
-        .map(|n| busy_graph.get_node(n).unwrap())
+        .map(|&n| busy_graph.external_key(n))
         .collect();
+    // edge properties are returned in the same order as the targets
+    let properties = query.properties.unwrap_or(false).then(|| {
+        outgoing
+            .iter()
+            .map(|&n| busy_graph.get_edge_properties(source, n, relation))
+            .collect()
+    });
 
-    Ok(Json(OutgoingEdgeResponse { targets }))
+    Ok(Json(OutgoingEdgeResponse {
+        targets,
+        properties,
+        next: next.map(|n| busy_graph.external_key(n)),
+    }))
 }
 
 #[derive(Serialize)]
 pub struct IncomingEdgeResponse {
-    sources: Vec<u32>,
+    sources: Vec<NodeKey>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<Vec<BTreeMap<String, PropertyValue>>>,
+    /// Cursor of the next page, if there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next: Option<NodeKey>,
 }
 
 #[derive(Deserialize)]
 pub struct IncomingEdgeQuery {
-    target: u32,
+    target: NodeKey,
+    relation: Option<String>,
+    label: Option<String>,
+    properties: Option<bool>,
+    since: Option<u64>,
+    until: Option<u64>,
 }
 
-/// Requests the incoming edges from a given target node-ID (of type `u32`). Returns a [`Vec<u32>`]
-/// which is the set of source node-IDs, or returns [`Errors::StillLoading`] when the graph is
-/// still loading.
+/// Requests the incoming edges from a given target [`NodeKey`]. Returns a [`Vec<NodeKey>`]
+/// which is the set of source node keys, optionally only those carrying a `label` or whose edge
+/// timestamp lies between `since` and `until`, and along with their edge `properties`. Sources
+/// can be paged or sampled as described on [`PageQuery`]. Returns [`Errors::StillLoading`] when
+/// the graph is still loading.
 pub async fn get_incoming_edges(
     state: Extension<GraphState>,
     Query(query): Query<IncomingEdgeQuery>,
+    Query(page): Query<PageQuery>,
 ) -> Result<Json<IncomingEdgeResponse>, Errors> {
     // Return Error if not loaded
     if !*state.graph.lock().unwrap().is_loaded.read().unwrap() {
         error!("Graph data not yet loaded!");
         return Err(Errors::StillLoading);
     }
-
-    let target = state.graph.lock().unwrap().get_node(query.target);
-    if target.is_none() {
-        warn!("source not present");
-        return Ok(Json(IncomingEdgeResponse { sources: vec![] }));
+    if !state.graph.lock().unwrap().is_directed() {
+        return Err(Errors::DirectionMismatch);
     }
 
+    let (target, relation) = {
+        let graph = state.graph.lock().unwrap();
+        (
+            graph
+                .resolve_key(&query.target)
+                .and_then(|nid| graph.get_node(nid)),
+            graph.resolve_relation(relation_name(&query.relation)),
+        )
+    };
+    let (Some(target), Some(relation)) = (target, relation) else {
+        warn!("target or relation not present");
+        return Ok(Json(IncomingEdgeResponse {
+            sources: vec![],
+            properties: None,
+            next: None,
+        }));
+    };
+
     // This feels very hacky.
     // I think there is a better way to ensure we don't spam lock the graph
     // I also think we may need to free the busy_graph?
     let busy_graph = state.graph.lock().unwrap();
-    let incoming = busy_graph.get_incoming_edges(target.unwrap());
+    let (incoming, next) = match page.selection(&busy_graph)? {
+        Some(selection) => {
+            let filter = NeighborFilter {
+                label: query.label.as_deref(),
+                since: query.since,
+                until: query.until,
+            };
+            let list = busy_graph.list_neighbors(
+                target,
+                relation,
+                Direction::Incoming,
+                &filter,
+                &selection,
+            );
+            (list.nodes, list.next)
+        }
+        None => {
+            let mut incoming = busy_graph.get_incoming_edges(target, relation);
+            if let Some(label) = &query.label {
+                busy_graph.retain_labeled(&mut incoming, label);
+            }
+            if query.since.is_some() || query.until.is_some() {
+                busy_graph.retain_edges_within(
+                    target,
+                    &mut incoming,
+                    relation,
+                    Direction::Incoming,
+                    query.since,
+                    query.until,
+                );
+            }
+            (incoming.iter().collect(), None)
+        }
+    };
     let sources: Vec<_> = incoming
         .iter()
-        .map(|n| busy_graph.get_node(n).unwrap())
+        .map(|&n| busy_graph.external_key(n))
         .collect();
+    // edge properties are returned in the same order as the sources
+    let properties = query.properties.unwrap_or(false).then(|| {
+        incoming
+            .iter()
+            .map(|&n| busy_graph.get_edge_properties(n, target, relation))
+            .collect()
+    });
+
+    Ok(Json(IncomingEdgeResponse {
+        sources,
+        properties,
+        next: next.map(|n| busy_graph.external_key(n)),
+    }))
+}
+
+#[derive(Serialize)]
+pub struct NeighborsResponse {
+    neighbors: Vec<NodeKey>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<Vec<BTreeMap<String, PropertyValue>>>,
+    /// Cursor of the next page, if there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next: Option<NodeKey>,
+}
+
+#[derive(Deserialize)]
+pub struct NeighborsQuery {
+    node: NodeKey,
+    relation: Option<String>,
+    label: Option<String>,
+    properties: Option<bool>,
+    since: Option<u64>,
+    until: Option<u64>,
+}
+
+/// Requests the neighbors of a given [`NodeKey`] in an undirected graph, with the same filters,
+/// paging and sampling as [`get_outgoing_edges`]. Returns [`Errors::DirectionMismatch`] on a
+/// directed graph and [`Errors::StillLoading`] when the graph is still loading.
+pub async fn get_neighbors(
+    state: Extension<GraphState>,
+    Query(query): Query<NeighborsQuery>,
+    Query(page): Query<PageQuery>,
+) -> Result<Json<NeighborsResponse>, Errors> {
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+    if graph.is_directed() {
+        return Err(Errors::DirectionMismatch);
+    }
 
-    Ok(Json(IncomingEdgeResponse { sources }))
+    let node = graph
+        .resolve_key(&query.node)
+        .and_then(|nid| graph.get_node(nid));
+    let relation = graph.resolve_relation(relation_name(&query.relation));
+    let (Some(node), Some(relation)) = (node, relation) else {
+        warn!("node or relation not present");
+        return Ok(Json(NeighborsResponse {
+            neighbors: vec![],
+            properties: None,
+            next: None,
+        }));
+    };
+
+    let (neighbors, next) = match page.selection(&graph)? {
+        Some(selection) => {
+            let filter = NeighborFilter {
+                label: query.label.as_deref(),
+                since: query.since,
+                until: query.until,
+            };
+            let list =
+                graph.list_neighbors(node, relation, Direction::Outgoing, &filter, &selection);
+            (list.nodes, list.next)
+        }
+        None => {
+            let mut neighbors = graph.get_neighbors(node, relation);
+            if let Some(label) = &query.label {
+                graph.retain_labeled(&mut neighbors, label);
+            }
+            if query.since.is_some() || query.until.is_some() {
+                graph.retain_edges_within(
+                    node,
+                    &mut neighbors,
+                    relation,
+                    Direction::Outgoing,
+                    query.since,
+                    query.until,
+                );
+            }
+            (neighbors.iter().collect::<Vec<_>>(), None)
+        }
+    };
+    // edge properties are returned in the same order as the neighbors
+    let properties = query.properties.unwrap_or(false).then(|| {
+        neighbors
+            .iter()
+            .map(|&n| graph.get_edge_properties(node, n, relation))
+            .collect()
+    });
+    let neighbors = neighbors.iter().map(|&n| graph.external_key(n)).collect();
+
+    Ok(Json(NeighborsResponse {
+        neighbors,
+        properties,
+        next: next.map(|n| graph.external_key(n)),
+    }))
 }
 
 #[derive(Serialize)]
@@ -157,8 +730,9 @@ pub struct HasEdgeResponse {
 
 #[derive(Deserialize)]
 pub struct HasEdgeQuery {
-    source: u32,
-    target: u32,
+    source: NodeKey,
+    target: NodeKey,
+    relation: Option<String>,
 }
 
 /// Queries the [`GraphState`] for a given edges, which is a source-target pair. Returns a `bool`
@@ -173,21 +747,75 @@ pub async fn get_has_edge(
         return Err(Errors::StillLoading);
     }
 
-    let source = state.graph.lock().unwrap().get_node(query.source);
-    let target = state.graph.lock().unwrap().get_node(query.target);
-    if source.is_none() || target.is_none() {
+    let source = state.graph.lock().unwrap().resolve_key(&query.source);
+    let target = state.graph.lock().unwrap().resolve_key(&query.target);
+    let relation = state
+        .graph
+        .lock()
+        .unwrap()
+        .resolve_relation(relation_name(&query.relation));
+    let (Some(source), Some(target), Some(relation)) = (source, target, relation) else {
         return Ok(Json(HasEdgeResponse { has_edge: false }));
-    }
+    };
 
     let has_edge = state
         .graph
         .lock()
         .unwrap()
-        .has_edge(source.unwrap(), target.unwrap());
+        .has_edge(source, target, relation);
     info!("{}", has_edge);
     Ok(Json(HasEdgeResponse { has_edge }))
 }
 
+#[derive(Serialize)]
+pub struct Change {
+    time: u64,
+    action: &'static str,
+    source: NodeKey,
+    target: NodeKey,
+    relation: String,
+}
+
+#[derive(Serialize)]
+pub struct ChangesResponse {
+    changes: Vec<Change>,
+}
+
+#[derive(Deserialize)]
+pub struct ChangesQuery {
+    since: u64,
+}
+
+/// Requests the changes applied to the graph after a unix time, in seconds, oldest first. The
+/// changes can be replayed by clients, or undone to view the graph at an earlier time.
+pub async fn get_changes(
+    state: Extension<GraphState>,
+    Query(query): Query<ChangesQuery>,
+) -> Json<ChangesResponse> {
+    let graph = state.graph.lock().unwrap();
+    let changes = graph
+        .get_changes_after(query.since)
+        .into_iter()
+        .map(|entry| Change {
+            time: entry.time,
+            action: match entry.action {
+                GraphAction::AddEdge => "add_edge",
+                GraphAction::RemoveEdge => "remove_edge",
+            },
+            source: graph.external_key(entry.source),
+            target: graph.external_key(entry.target),
+            relation: graph.relation_name(entry.relation).unwrap_or_default(),
+        })
+        .collect();
+
+    Json(ChangesResponse { changes })
+}
+
+/// Falls back to [`DEFAULT_RELATION`] when a request does not name a relation.
+pub(crate) fn relation_name(relation: &Option<String>) -> &str {
+    relation.as_deref().unwrap_or(DEFAULT_RELATION)
+}
+
 pub async fn get_flush_updates(state: Extension<GraphState>) -> impl IntoResponse {
     match state.graph.lock().unwrap().flush_updates() {
         Ok(_) => StatusCode::OK,
@@ -0,0 +1,204 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use axum::{http::StatusCode, Extension, Json};
+use raphle_experimental::{change_log, relation::DEFAULT_RELATION};
+use serde::{Deserialize, Serialize, Serializer};
+use tracing::{error, info, warn};
+
+use crate::{graphs, Errors, GraphRegistry, GraphState};
+
+/// Directory graphs are reloaded from. Paths given to `/admin/reload` are relative to it, and
+/// must not lead out of it.
+#[derive(Clone)]
+pub struct ReloadDir(Arc<PathBuf>);
+
+impl ReloadDir {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        ReloadDir(Arc::new(dir.into()))
+    }
+
+    /// Resolves a path to a file inside the directory, following `..` and symlinks.
+    fn resolve(&self, path: &str) -> Option<PathBuf> {
+        let dir = self.0.canonicalize().ok()?;
+        let resolved = dir.join(Path::new(path)).canonicalize().ok()?;
+        (resolved.starts_with(&dir) && resolved.is_file()).then_some(resolved)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReloadPhase {
+    /// The new graph is being loaded from its input file.
+    Loading,
+    /// Writes received during the load are being applied to the new graph.
+    Replaying,
+    Done,
+    Failed,
+}
+
+/// Progress of a reload, reported through `/health`.
+#[derive(Clone, Serialize)]
+pub struct ReloadStatus {
+    phase: ReloadPhase,
+    path: String,
+    /// Unix time, in seconds, at which the reload started.
+    started_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    finished_at: Option<u64>,
+    #[serde(serialize_with = "serialize_count")]
+    rows_loaded: Arc<AtomicUsize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+fn serialize_count<S: Serializer>(
+    count: &Arc<AtomicUsize>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(count.load(Ordering::Relaxed) as u64)
+}
+
+#[derive(Deserialize)]
+pub struct ReloadBody {
+    /// Path of a space-delimited edge file, relative to the [`ReloadDir`].
+    path: String,
+    relation: Option<String>,
+    #[serde(default)]
+    property_columns: Vec<String>,
+}
+
+/// Replaces the edges of a graph with those of a new input file without downtime. The new graph
+/// is loaded in the background while the old one keeps serving. Writes received in the meantime
+/// are then replayed onto the new graph, along with all node labels and properties, and the
+/// graphs are swapped, carrying over the expiries of temporary edges and dropping everything
+/// computed for the old graph. Rows of nodes gone from the new file are deleted from the
+/// database on the next flush. Progress is reported through `/health`. Returns
+/// [`Errors::InvalidReloadPath`] for paths outside the [`ReloadDir`], and
+/// [`Errors::ReloadInProgress`] when the graph is already reloading.
+pub async fn post_reload(
+    state: Extension<GraphState>,
+    Extension(reload_dir): Extension<ReloadDir>,
+    Json(body): Json<ReloadBody>,
+) -> Result<StatusCode, Errors> {
+    let Some(path) = reload_dir.resolve(&body.path) else {
+        warn!("reload path is not a file in the data directory");
+        return Err(Errors::InvalidReloadPath);
+    };
+
+    {
+        let mut reload = state.reload.lock().unwrap();
+        if reload.as_ref().is_some_and(|reload| {
+            matches!(reload.phase, ReloadPhase::Loading | ReloadPhase::Replaying)
+        }) {
+            return Err(Errors::ReloadInProgress);
+        }
+        *reload = Some(ReloadStatus {
+            phase: ReloadPhase::Loading,
+            path: body.path.clone(),
+            started_at: change_log::now(),
+            finished_at: None,
+            rows_loaded: Arc::default(),
+            error: None,
+        });
+    }
+
+    // Only built once the reload is claimed, since copying the key mapping of a large graph
+    // takes a while. `/health` takes the graph lock before the reload lock, so the two are not
+    // held together here
+    let (mut reloaded_graph, offset) = {
+        let graph = state.graph.lock().unwrap();
+        (graph.empty_like(), graph.change_offset())
+    };
+    if let Some(reload) = state.reload.lock().unwrap().as_mut() {
+        reload.rows_loaded = reloaded_graph.load_progress();
+    }
+
+    let state = state.0.clone();
+    tokio::task::spawn_blocking(move || {
+        let relation = body.relation.as_deref().unwrap_or(DEFAULT_RELATION);
+        let property_columns: Vec<&str> =
+            body.property_columns.iter().map(String::as_str).collect();
+        let loaded = reloaded_graph.load_from_csv(
+            &path.to_string_lossy(),
+            Some(b' '),
+            relation,
+            &property_columns,
+        );
+
+        let finish = |phase, error| {
+            if let Some(reload) = state.reload.lock().unwrap().as_mut() {
+                reload.phase = phase;
+                reload.finished_at = Some(change_log::now());
+                reload.error = error;
+            }
+        };
+        if let Err(e) = loaded {
+            // keep serving the old graph
+            error!("failed to reload graph from {}: {}", body.path, e);
+            // the error may quote the input file, so only its kind is reported
+            let reason = format!("failed to load input file: {}", e.kind());
+            finish(ReloadPhase::Failed, Some(reason));
+            return;
+        }
+
+        if let Some(reload) = state.reload.lock().unwrap().as_mut() {
+            reload.phase = ReloadPhase::Replaying;
+        }
+        // Holding the graph lock while replaying means no write lands on the old graph after it
+        // was replayed
+        let mut graph = state.graph.lock().unwrap();
+        graph.replay_changes_onto(&reloaded_graph, offset);
+        *graph = reloaded_graph;
+        state.clear_computed();
+        drop(graph);
+
+        finish(ReloadPhase::Done, None);
+        info!("reloaded graph from {}", body.path);
+    });
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[derive(Deserialize)]
+pub struct ReplayBody {
+    /// Unix time, in seconds, to replay the graph to.
+    until: u64,
+    /// Name the replayed graph is served under.
+    name: String,
+}
+
+/// Rebuilds the graph as it was at a unix time by undoing the changes logged since, and serves
+/// it as a new graph under `/graphs/{name}`, e.g. to look at the graph as of last week. Only
+/// edges are played back. Returns [`Errors::ChangesTruncated`] when changes since then were
+/// already dropped from the change log, and [`Errors::GraphFileExists`] when a dropped graph
+/// left its database file under the name.
+pub async fn post_replay(
+    state: Extension<GraphState>,
+    Extension(registry): Extension<GraphRegistry>,
+    Json(body): Json<ReplayBody>,
+) -> Result<StatusCode, Errors> {
+    graphs::check_available(&registry, &body.name)?;
+    if !*state.graph.lock().unwrap().is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    // copying the adjacency of a large graph takes a while
+    let graph = state.graph.clone();
+    let until = body.until;
+    let replayed = tokio::task::spawn_blocking(move || graph.lock().unwrap().replay_until(until))
+        .await
+        .unwrap()
+        .ok_or(Errors::ChangesTruncated)?
+        .with_db_path(&graphs::db_path(&body.name));
+
+    registry.insert(&body.name, GraphState::new(Arc::new(Mutex::new(replayed))))?;
+    info!("replayed graph to {} as {}", body.until, body.name);
+    Ok(StatusCode::CREATED)
+}
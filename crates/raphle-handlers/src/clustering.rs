@@ -0,0 +1,154 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::Query,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use raphle_experimental::{
+    clustering::{self, Clustering, NodeClustering},
+    id_map::NodeKey,
+    relation::RelationId,
+    snapshot::Snapshot,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::{
+    action::relation_name,
+    jobs::{self, Computed, JobCache},
+    Errors, GraphState,
+};
+
+/// Most clustering results kept per graph.
+pub(crate) const MAX_CACHED_CLUSTERINGS: usize = 16;
+
+/// Triangle counts of a graph, by relation.
+pub type ClusteringCache = JobCache<RelationId, Clustering>;
+
+#[derive(Deserialize)]
+pub struct NodeClusteringQuery {
+    node: NodeKey,
+    relation: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct NodeClusteringResponse {
+    node: NodeKey,
+    degree: u64,
+    triangles: u64,
+    coefficient: f64,
+}
+
+/// Requests the number of triangles through a node and its local clustering coefficient, with
+/// edges taken in either direction. Computed on demand. Returns [`Errors::StillLoading`] when
+/// the graph is still loading.
+pub async fn get_node_clustering(
+    state: Extension<GraphState>,
+    Query(query): Query<NodeClusteringQuery>,
+) -> Result<Json<NodeClusteringResponse>, Errors> {
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let node = graph
+        .resolve_key(&query.node)
+        .and_then(|nid| graph.get_node(nid));
+    let relation = graph.resolve_relation(relation_name(&query.relation));
+    let (Some(node), Some(relation)) = (node, relation) else {
+        warn!("node or relation not present");
+        return Ok(Json(NodeClusteringResponse {
+            node: query.node,
+            degree: 0,
+            triangles: 0,
+            coefficient: 0.0,
+        }));
+    };
+
+    let NodeClustering {
+        degree,
+        triangles,
+        coefficient,
+    } = clustering::node_clustering(&graph, node, relation);
+    Ok(Json(NodeClusteringResponse {
+        node: query.node,
+        degree,
+        triangles,
+        coefficient,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct ClusteringQuery {
+    relation: Option<String>,
+    /// Recounts the triangles even if they are cached.
+    #[serde(default)]
+    refresh: bool,
+}
+
+#[derive(Serialize)]
+pub struct ClusteringResponse {
+    computed_at: u64,
+    triangles: u64,
+    transitivity: f64,
+    average_coefficient: f64,
+}
+
+/// Requests the number of triangles of the whole graph, its transitivity and its average local
+/// clustering coefficient. They are computed in the background on a snapshot of the graph and
+/// cached; until they are ready, `202 Accepted` is returned. Returns [`Errors::StillLoading`]
+/// when the graph is still loading.
+pub async fn get_clustering(
+    state: Extension<GraphState>,
+    Query(query): Query<ClusteringQuery>,
+) -> Result<Response, Errors> {
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let Some(relation) = graph.resolve_relation(relation_name(&query.relation)) else {
+        warn!("relation not present");
+        return Ok(Json(ClusteringResponse {
+            computed_at: 0,
+            triangles: 0,
+            transitivity: 0.0,
+            average_coefficient: 0.0,
+        })
+        .into_response());
+    };
+    let Some(result) = cached_or_scheduled(&state, relation, query.refresh) else {
+        return Ok(jobs::pending());
+    };
+
+    Ok(Json(ClusteringResponse {
+        computed_at: result.computed_at,
+        triangles: result.value.triangles,
+        transitivity: result.value.transitivity,
+        average_coefficient: result.value.average_coefficient,
+    })
+    .into_response())
+}
+
+/// Returns the cached triangle counts of a relation, computing them in the background if
+/// needed.
+fn cached_or_scheduled(
+    state: &GraphState,
+    relation: RelationId,
+    refresh: bool,
+) -> Option<Arc<Computed<Clustering>>> {
+    let graph = state.graph.clone();
+    jobs::cached_or_scheduled(&state.clustering, relation, refresh, move || {
+        let snapshot = Snapshot::of(&graph.lock().unwrap(), relation);
+        let clustering = clustering::clustering(&snapshot);
+        info!(
+            "counted {} triangles among {} nodes",
+            clustering.triangles,
+            snapshot.len()
+        );
+        clustering
+    })
+}
@@ -0,0 +1,244 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::Query,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use raphle_experimental::{
+    community::{self, Communities, CommunityAlgorithm},
+    id_map::NodeKey,
+    relation::RelationId,
+    snapshot::Snapshot,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::{
+    action::relation_name,
+    jobs::{self, Computed, JobCache},
+    Errors, GraphState,
+};
+
+/// Most community partitions kept per graph.
+pub(crate) const MAX_CACHED_COMMUNITIES: usize = 16;
+
+/// Largest number of communities listed by [`get_communities`] when no `limit` is given.
+const DEFAULT_COMMUNITIES_LIMIT: usize = 10;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct CommunitiesKey {
+    relation: RelationId,
+    algorithm: CommunityAlgorithm,
+    weight_property: String,
+    seed: u64,
+}
+
+/// Communities detected in a graph, by relation, algorithm, edge weights and seed.
+pub type CommunityCache = JobCache<CommunitiesKey, Communities>;
+
+#[derive(Deserialize)]
+pub struct CommunityQuery {
+    /// Node whose community is asked for.
+    node: Option<NodeKey>,
+    /// Community asked for, when no `node` is given.
+    community: Option<u32>,
+    #[serde(default)]
+    algorithm: CommunityAlgorithm,
+    relation: Option<String>,
+    /// Edge property holding the weights, `weight` by default.
+    weight: Option<String>,
+    #[serde(default)]
+    seed: u64,
+    /// Largest number of members to return; `size` is always that of the whole community.
+    limit: Option<usize>,
+    /// Detects the communities again even if they are cached.
+    #[serde(default)]
+    refresh: bool,
+}
+
+#[derive(Serialize)]
+pub struct CommunityResponse {
+    computed_at: u64,
+    community: Option<u32>,
+    size: u64,
+    members: Vec<NodeKey>,
+}
+
+/// Requests the community of a `node`, or a `community` by number, and its members. Communities
+/// are detected with `louvain` or `label_propagation` in the background on a snapshot of the
+/// graph and cached; until they are ready, `202 Accepted` is returned. Edges are weighted by
+/// their `weight` property where they have one. Returns [`Errors::StillLoading`] when the graph
+/// is still loading.
+pub async fn get_community(
+    state: Extension<GraphState>,
+    Query(query): Query<CommunityQuery>,
+) -> Result<Response, Errors> {
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let empty = CommunityResponse {
+        computed_at: 0,
+        community: None,
+        size: 0,
+        members: vec![],
+    };
+    let Some(relation) = graph.resolve_relation(relation_name(&query.relation)) else {
+        warn!("relation not present");
+        return Ok(Json(empty).into_response());
+    };
+    let key = CommunitiesKey {
+        relation,
+        algorithm: query.algorithm,
+        weight_property: query.weight.unwrap_or_else(|| "weight".to_string()),
+        seed: query.seed,
+    };
+    let Some(result) = cached_or_scheduled(&state, key, query.refresh) else {
+        return Ok(jobs::pending());
+    };
+
+    let partition = &result.value.partition;
+    let community = match &query.node {
+        Some(node) => graph
+            .resolve_key(node)
+            .and_then(|nid| partition.component_of(nid)),
+        None => query
+            .community
+            .filter(|&community| (community as usize) < partition.count()),
+    };
+    let Some(community) = community else {
+        warn!("community not present");
+        return Ok(Json(CommunityResponse {
+            computed_at: result.computed_at,
+            ..empty
+        })
+        .into_response());
+    };
+    let members = partition
+        .members(community)
+        .iter()
+        .take(query.limit.unwrap_or(usize::MAX))
+        .map(|nid| graph.external_key(nid))
+        .collect();
+
+    Ok(Json(CommunityResponse {
+        computed_at: result.computed_at,
+        community: Some(community),
+        size: partition.size(community),
+        members,
+    })
+    .into_response())
+}
+
+#[derive(Deserialize)]
+pub struct CommunitiesQuery {
+    #[serde(default)]
+    algorithm: CommunityAlgorithm,
+    relation: Option<String>,
+    /// Edge property holding the weights, `weight` by default.
+    weight: Option<String>,
+    #[serde(default)]
+    seed: u64,
+    /// Largest number of communities to list, largest first.
+    limit: Option<usize>,
+    /// Detects the communities again even if they are cached.
+    #[serde(default)]
+    refresh: bool,
+}
+
+#[derive(Serialize)]
+pub struct CommunitySize {
+    community: u32,
+    size: u64,
+}
+
+#[derive(Serialize)]
+pub struct CommunitiesResponse {
+    computed_at: u64,
+    count: usize,
+    modularity: f64,
+    /// The largest communities, largest first.
+    largest: Vec<CommunitySize>,
+}
+
+/// Requests the number of communities of the graph, the modularity of the partition and its
+/// largest communities, detected like those of [`get_community`].
+pub async fn get_communities(
+    state: Extension<GraphState>,
+    Query(query): Query<CommunitiesQuery>,
+) -> Result<Response, Errors> {
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let Some(relation) = graph.resolve_relation(relation_name(&query.relation)) else {
+        warn!("relation not present");
+        return Ok(Json(CommunitiesResponse {
+            computed_at: 0,
+            count: 0,
+            modularity: 0.0,
+            largest: vec![],
+        })
+        .into_response());
+    };
+    let key = CommunitiesKey {
+        relation,
+        algorithm: query.algorithm,
+        weight_property: query.weight.unwrap_or_else(|| "weight".to_string()),
+        seed: query.seed,
+    };
+    let Some(result) = cached_or_scheduled(&state, key, query.refresh) else {
+        return Ok(jobs::pending());
+    };
+
+    let partition = &result.value.partition;
+    let mut largest: Vec<CommunitySize> = (0..partition.count() as u32)
+        .map(|community| CommunitySize {
+            community,
+            size: partition.size(community),
+        })
+        .collect();
+    largest.sort_by(|a, b| b.size.cmp(&a.size).then(a.community.cmp(&b.community)));
+    largest.truncate(query.limit.unwrap_or(DEFAULT_COMMUNITIES_LIMIT));
+
+    Ok(Json(CommunitiesResponse {
+        computed_at: result.computed_at,
+        count: partition.count(),
+        modularity: result.value.modularity,
+        largest,
+    })
+    .into_response())
+}
+
+/// Returns the cached communities for a key, detecting them in the background if needed.
+fn cached_or_scheduled(
+    state: &GraphState,
+    key: CommunitiesKey,
+    refresh: bool,
+) -> Option<Arc<Computed<Communities>>> {
+    let graph = state.graph.clone();
+    let job_key = key.clone();
+    jobs::cached_or_scheduled(&state.communities, key, refresh, move || {
+        let snapshot = {
+            let graph = graph.lock().unwrap();
+            Snapshot::of(&graph, job_key.relation).weighted(
+                &graph,
+                job_key.relation,
+                &job_key.weight_property,
+            )
+        };
+        let communities = community::communities(&snapshot, job_key.algorithm, job_key.seed);
+        info!(
+            "found {} communities among {} nodes with a modularity of {}",
+            communities.partition.count(),
+            snapshot.len(),
+            communities.modularity
+        );
+        communities
+    })
+}
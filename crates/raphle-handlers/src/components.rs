@@ -0,0 +1,214 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::Query,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use raphle_experimental::{
+    components::{self, Components},
+    id_map::NodeKey,
+    relation::RelationId,
+    snapshot::Snapshot,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::{
+    action::relation_name,
+    jobs::{self, Computed, JobCache},
+    Errors, GraphState,
+};
+
+/// Most component partitions kept per graph.
+pub(crate) const MAX_CACHED_COMPONENTS: usize = 16;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComponentKind {
+    /// Nodes linked by edges in either direction.
+    #[default]
+    Weak,
+    /// Nodes that can all reach each other along outgoing edges.
+    Strong,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ComponentsKey {
+    relation: RelationId,
+    kind: ComponentKind,
+}
+
+/// Connected components of a graph, by relation and kind.
+pub type ComponentCache = JobCache<ComponentsKey, Components>;
+
+#[derive(Deserialize)]
+pub struct ComponentQuery {
+    node: NodeKey,
+    #[serde(default)]
+    kind: ComponentKind,
+    relation: Option<String>,
+    /// Largest number of members to return; `size` is always that of the whole component.
+    limit: Option<usize>,
+    /// Recomputes the components even if they are cached.
+    #[serde(default)]
+    refresh: bool,
+}
+
+#[derive(Serialize)]
+pub struct ComponentResponse {
+    computed_at: u64,
+    component: Option<u32>,
+    size: u64,
+    members: Vec<NodeKey>,
+}
+
+/// Requests the `weak` or `strong` component of a node and its members. Components are computed
+/// in the background on a snapshot of the graph and cached; until they are ready,
+/// `202 Accepted` is returned. Returns [`Errors::StillLoading`] when the graph is still loading.
+pub async fn get_component(
+    state: Extension<GraphState>,
+    Query(query): Query<ComponentQuery>,
+) -> Result<Response, Errors> {
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let empty = ComponentResponse {
+        computed_at: 0,
+        component: None,
+        size: 0,
+        members: vec![],
+    };
+    let Some(relation) = graph.resolve_relation(relation_name(&query.relation)) else {
+        warn!("relation not present");
+        return Ok(Json(empty).into_response());
+    };
+    let key = ComponentsKey {
+        relation,
+        kind: query.kind,
+    };
+    let Some(result) = cached_or_scheduled(&state, key, query.refresh) else {
+        return Ok(jobs::pending());
+    };
+
+    let component = graph
+        .resolve_key(&query.node)
+        .and_then(|nid| result.value.component_of(nid));
+    let Some(component) = component else {
+        warn!("node not present");
+        return Ok(Json(ComponentResponse {
+            computed_at: result.computed_at,
+            ..empty
+        })
+        .into_response());
+    };
+    let members = result
+        .value
+        .members(component)
+        .iter()
+        .take(query.limit.unwrap_or(usize::MAX))
+        .map(|nid| graph.external_key(nid))
+        .collect();
+
+    Ok(Json(ComponentResponse {
+        computed_at: result.computed_at,
+        component: Some(component),
+        size: result.value.size(component),
+        members,
+    })
+    .into_response())
+}
+
+#[derive(Deserialize)]
+pub struct ComponentsQuery {
+    #[serde(default)]
+    kind: ComponentKind,
+    relation: Option<String>,
+    /// Recomputes the components even if they are cached.
+    #[serde(default)]
+    refresh: bool,
+}
+
+#[derive(Serialize)]
+pub struct SizeCount {
+    size: u64,
+    count: u64,
+}
+
+#[derive(Serialize)]
+pub struct ComponentsResponse {
+    computed_at: u64,
+    count: usize,
+    largest: u64,
+    /// Number of components of each size, smallest first.
+    histogram: Vec<SizeCount>,
+}
+
+/// Requests the number of `weak` or `strong` components of the graph and a histogram of their
+/// sizes, computed like those of [`get_component`].
+pub async fn get_components(
+    state: Extension<GraphState>,
+    Query(query): Query<ComponentsQuery>,
+) -> Result<Response, Errors> {
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let Some(relation) = graph.resolve_relation(relation_name(&query.relation)) else {
+        warn!("relation not present");
+        return Ok(Json(ComponentsResponse {
+            computed_at: 0,
+            count: 0,
+            largest: 0,
+            histogram: vec![],
+        })
+        .into_response());
+    };
+    let key = ComponentsKey {
+        relation,
+        kind: query.kind,
+    };
+    let Some(result) = cached_or_scheduled(&state, key, query.refresh) else {
+        return Ok(jobs::pending());
+    };
+
+    let histogram = result.value.size_histogram();
+    Ok(Json(ComponentsResponse {
+        computed_at: result.computed_at,
+        count: result.value.count(),
+        largest: histogram.keys().next_back().copied().unwrap_or(0),
+        histogram: histogram
+            .into_iter()
+            .map(|(size, count)| SizeCount { size, count })
+            .collect(),
+    })
+    .into_response())
+}
+
+/// Returns the cached components for a key, computing them in the background if needed.
+fn cached_or_scheduled(
+    state: &GraphState,
+    key: ComponentsKey,
+    refresh: bool,
+) -> Option<Arc<Computed<Components>>> {
+    let graph = state.graph.clone();
+    let job_key = key.clone();
+    jobs::cached_or_scheduled(&state.components, key, refresh, move || {
+        let snapshot = Snapshot::of(&graph.lock().unwrap(), job_key.relation);
+        let components = match job_key.kind {
+            ComponentKind::Weak => components::weakly_connected(&snapshot),
+            ComponentKind::Strong => components::strongly_connected(&snapshot),
+        };
+        info!(
+            "found {} components among {} nodes",
+            components.count(),
+            snapshot.len()
+        );
+        components
+    })
+}
@@ -0,0 +1,172 @@
+use axum::{extract::Query, Extension, Json};
+use raphle_experimental::{id_map::NodeKey, rwlocked_graph::Direction};
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use crate::{action::relation_name, Errors, GraphState};
+
+/// Most nodes a single request may ask for.
+const MAX_DEGREE_LIMIT: usize = 1000;
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DegreeDirection {
+    /// Edges to the node, e.g. followers.
+    #[default]
+    In,
+    /// Edges from the node, e.g. follows.
+    Out,
+}
+
+impl From<DegreeDirection> for Direction {
+    fn from(direction: DegreeDirection) -> Self {
+        match direction {
+            DegreeDirection::In => Direction::Incoming,
+            DegreeDirection::Out => Direction::Outgoing,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DegreeQuery {
+    node: NodeKey,
+    relation: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct DegreeResponse {
+    node: NodeKey,
+    in_degree: u64,
+    out_degree: u64,
+}
+
+/// Requests the number of incoming and outgoing edges of a node, counted from the cardinality
+/// of its neighbor sets rather than by listing them. Both are the number of edges of the node
+/// in an undirected graph. Returns [`Errors::StillLoading`] when the graph is still loading.
+pub async fn get_degree(
+    state: Extension<GraphState>,
+    Query(query): Query<DegreeQuery>,
+) -> Result<Json<DegreeResponse>, Errors> {
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let node = graph
+        .resolve_key(&query.node)
+        .and_then(|nid| graph.get_node(nid));
+    let relation = graph.resolve_relation(relation_name(&query.relation));
+    let (Some(node), Some(relation)) = (node, relation) else {
+        warn!("node or relation not present");
+        return Ok(Json(DegreeResponse {
+            node: query.node,
+            in_degree: 0,
+            out_degree: 0,
+        }));
+    };
+
+    Ok(Json(DegreeResponse {
+        node: query.node,
+        in_degree: graph.degree(node, relation, Direction::Incoming),
+        out_degree: graph.degree(node, relation, Direction::Outgoing),
+    }))
+}
+
+fn default_limit() -> usize {
+    10
+}
+
+#[derive(Deserialize)]
+pub struct DegreeTopQuery {
+    #[serde(default)]
+    direction: DegreeDirection,
+    relation: Option<String>,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+#[derive(Serialize)]
+pub struct NodeDegree {
+    node: NodeKey,
+    degree: u64,
+}
+
+#[derive(Serialize)]
+pub struct DegreeTopResponse {
+    nodes: Vec<NodeDegree>,
+}
+
+/// Requests the `limit` nodes with the most `in` or `out` edges, from an index kept up to date
+/// as edges are added and removed. Edges that expired count until they are reaped. Returns
+/// [`Errors::InvalidQuery`] for a `limit` above [`MAX_DEGREE_LIMIT`].
+pub async fn get_degree_top(
+    state: Extension<GraphState>,
+    Query(query): Query<DegreeTopQuery>,
+) -> Result<Json<DegreeTopResponse>, Errors> {
+    if query.limit > MAX_DEGREE_LIMIT {
+        return Err(Errors::InvalidQuery);
+    }
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let Some(relation) = graph.resolve_relation(relation_name(&query.relation)) else {
+        warn!("relation not present");
+        return Ok(Json(DegreeTopResponse { nodes: vec![] }));
+    };
+    let nodes = graph
+        .top_by_degree(relation, query.direction.into(), query.limit)
+        .into_iter()
+        .map(|(nid, degree)| NodeDegree {
+            node: graph.external_key(nid),
+            degree,
+        })
+        .collect();
+    Ok(Json(DegreeTopResponse { nodes }))
+}
+
+#[derive(Deserialize)]
+pub struct DegreeHistogramQuery {
+    #[serde(default)]
+    direction: DegreeDirection,
+    relation: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct DegreeCount {
+    degree: u64,
+    count: u64,
+}
+
+#[derive(Serialize)]
+pub struct DegreeHistogramResponse {
+    /// Number of nodes of each degree, lowest first. Nodes without edges are left out.
+    histogram: Vec<DegreeCount>,
+}
+
+/// Requests the distribution of `in` or `out` degrees over the graph, read from the same index
+/// as [`get_degree_top`].
+pub async fn get_degree_histogram(
+    state: Extension<GraphState>,
+    Query(query): Query<DegreeHistogramQuery>,
+) -> Result<Json<DegreeHistogramResponse>, Errors> {
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let Some(relation) = graph.resolve_relation(relation_name(&query.relation)) else {
+        warn!("relation not present");
+        return Ok(Json(DegreeHistogramResponse { histogram: vec![] }));
+    };
+    let histogram = graph
+        .degree_histogram(relation, query.direction.into())
+        .into_iter()
+        .map(|(degree, count)| DegreeCount { degree, count })
+        .collect();
+    Ok(Json(DegreeHistogramResponse { histogram }))
+}
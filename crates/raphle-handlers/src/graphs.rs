@@ -0,0 +1,192 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{Path, Query, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+    Extension, Json,
+};
+use raphle_experimental::rwlocked_graph::{RwLockedGraph, DEFAULT_DB_PATH};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::{Errors, GraphRegistry, GraphState};
+
+/// Looks up the graph named by a `/graphs/{name}/...` route and hands it to the graph handlers
+/// as their [`GraphState`]. Returns [`Errors::GraphNotFound`] for unknown graphs.
+pub async fn scope_graph(
+    Extension(registry): Extension<GraphRegistry>,
+    Path(name): Path<String>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, Errors> {
+    let state = registry.get(&name).ok_or(Errors::GraphNotFound)?;
+    // only registered graphs are labelled, so requests for unknown names add no series
+    metrics::counter!("raphle_graph_requests_total", "graph" => name).increment(1);
+    request.extensions_mut().insert(state);
+    Ok(next.run(request).await)
+}
+
+/// Name the graph loaded on startup is served under. It cannot be dropped.
+pub const DEFAULT_GRAPH: &str = "default";
+
+/// Checks that a graph name is made of ASCII letters, digits, `-` and `_` only, since it also
+/// names the database file, and that the file is not [`DEFAULT_DB_PATH`], which belongs to the
+/// graph loaded on startup.
+fn check_name(name: &str) -> Result<(), Errors> {
+    let is_valid_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && db_path(name) != DEFAULT_DB_PATH;
+    if !is_valid_name {
+        return Err(Errors::InvalidGraphName);
+    }
+    Ok(())
+}
+
+/// Checks that a new graph can be created under a name: the name is valid, not taken, and has
+/// no database file left over from a graph dropped without `purge`, whose rows the new graph
+/// would mix with its own.
+pub(crate) fn check_available(registry: &GraphRegistry, name: &str) -> Result<(), Errors> {
+    check_name(name)?;
+    if registry.get(name).is_some() {
+        return Err(Errors::GraphExists);
+    }
+    if std::path::Path::new(&db_path(name)).exists() {
+        return Err(Errors::GraphFileExists);
+    }
+    Ok(())
+}
+
+/// Database file of a named graph.
+pub fn db_path(name: &str) -> String {
+    format!("data/{}.db", name)
+}
+
+#[derive(Serialize)]
+pub struct GraphInfo {
+    name: String,
+    loaded: bool,
+    directed: bool,
+}
+
+#[derive(Serialize)]
+pub struct GraphsResponse {
+    graphs: Vec<GraphInfo>,
+}
+
+/// Lists the registered graphs, sorted by name.
+pub async fn list_graphs(Extension(registry): Extension<GraphRegistry>) -> Json<GraphsResponse> {
+    let graphs = registry
+        .all()
+        .into_iter()
+        .map(|(name, state)| {
+            let graph = state.graph.lock().unwrap();
+            let loaded = *graph.is_loaded.read().unwrap();
+            GraphInfo {
+                name,
+                loaded,
+                directed: graph.is_directed(),
+            }
+        })
+        .collect();
+
+    Json(GraphsResponse { graphs })
+}
+
+fn default_directed() -> bool {
+    true
+}
+
+fn default_expected_node_count() -> u32 {
+    1_000
+}
+
+#[derive(Deserialize)]
+pub struct CreateGraphBody {
+    name: String,
+    #[serde(default = "default_directed")]
+    directed: bool,
+    #[serde(default)]
+    id_mapping: bool,
+    #[serde(default = "default_expected_node_count")]
+    expected_node_count: u32,
+}
+
+/// Creates an empty graph, persisted to its own database file. The graph can be written to right
+/// away, or filled from a file through `/graphs/{name}/admin/reload`. Returns
+/// [`Errors::GraphFileExists`] when the database file is left over from a graph dropped without
+/// `purge`.
+pub async fn post_graph(
+    Extension(registry): Extension<GraphRegistry>,
+    Json(body): Json<CreateGraphBody>,
+) -> Result<StatusCode, Errors> {
+    check_available(&registry, &body.name)?;
+
+    let graph = if body.id_mapping {
+        RwLockedGraph::with_id_mapping(body.expected_node_count)
+    } else {
+        RwLockedGraph::new(body.expected_node_count)
+    };
+    let graph = if body.directed {
+        graph
+    } else {
+        graph.undirected()
+    };
+    let graph = graph.with_db_path(&db_path(&body.name));
+    *graph.is_loaded.write().unwrap() = true;
+
+    registry.insert(&body.name, GraphState::new(Arc::new(Mutex::new(graph))))?;
+    info!("created graph {}", body.name);
+    Ok(StatusCode::CREATED)
+}
+
+#[derive(Deserialize)]
+pub struct DeleteGraphQuery {
+    /// Also removes the database file, so the name can be used for a new graph.
+    #[serde(default)]
+    purge: bool,
+}
+
+/// Drops a graph from memory. Its database file is kept, unless `purge` is set. Returns
+/// [`Errors::DefaultGraph`] for the graph loaded on startup.
+pub async fn delete_graph(
+    Extension(registry): Extension<GraphRegistry>,
+    Path(name): Path<String>,
+    Query(query): Query<DeleteGraphQuery>,
+) -> Result<StatusCode, Errors> {
+    if name == DEFAULT_GRAPH {
+        return Err(Errors::DefaultGraph);
+    }
+    let state = registry.remove(&name).ok_or(Errors::GraphNotFound)?;
+    info!("dropped graph {}", name);
+
+    if query.purge {
+        // requests that still hold the graph may flush to the file, so wait for them first
+        let _graph = state.graph.lock().unwrap();
+        purge_db(&db_path(&name))?;
+        info!("removed database file of graph {}", name);
+    }
+    Ok(StatusCode::OK)
+}
+
+/// Removes a database file along with its WAL files.
+fn purge_db(path: &str) -> Result<(), Errors> {
+    for suffix in ["", "-wal", "-shm"] {
+        match fs::remove_file(format!("{}{}", path, suffix)) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => {
+                error!("failed to remove {}{}: {}", path, suffix, e);
+                return Err(Errors::PurgeFailed);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use raphle_experimental::change_log;
+use serde::Serialize;
+
+/// The result of a background computation.
+pub struct Computed<V> {
+    pub value: V,
+    /// Unix time, in seconds, at which the computation finished.
+    pub computed_at: u64,
+}
+
+struct JobEntry<V> {
+    result: Option<Arc<Computed<V>>>,
+    computing: bool,
+}
+
+/// Results of background computations over a graph, by the parameters they were computed with.
+/// Past its capacity, the result computed longest ago is dropped.
+pub struct JobCache<K, V> {
+    entries: HashMap<K, JobEntry<V>>,
+    capacity: usize,
+    /// Counts how often the cache was cleared, so jobs started before are not stored.
+    generation: u64,
+}
+
+impl<K: Clone + Eq + Hash, V> JobCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        JobCache {
+            entries: HashMap::new(),
+            capacity,
+            generation: 0,
+        }
+    }
+
+    /// Drops every result, including those of jobs that are still running once they finish.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.generation += 1;
+    }
+
+    fn store(&mut self, key: K, value: V) {
+        let result = Computed {
+            value,
+            computed_at: change_log::now(),
+        };
+        self.entries.insert(
+            key.clone(),
+            JobEntry {
+                result: Some(Arc::new(result)),
+                computing: false,
+            },
+        );
+        if self.entries.len() <= self.capacity {
+            return;
+        }
+
+        let oldest = self
+            .entries
+            .iter()
+            .filter(|(other, entry)| **other != key && !entry.computing)
+            .min_by_key(|(_, entry)| entry.result.as_ref().map_or(0, |r| r.computed_at))
+            .map(|(other, _)| other.clone());
+        if let Some(oldest) = oldest {
+            self.entries.remove(&oldest);
+        }
+    }
+}
+
+/// Marks a job as no longer computing once it ends, also when it panics, so that it can be
+/// scheduled again.
+struct Computing<K: Clone + Eq + Hash, V> {
+    cache: Arc<Mutex<JobCache<K, V>>>,
+    key: K,
+    generation: u64,
+}
+
+impl<K: Clone + Eq + Hash, V> Drop for Computing<K, V> {
+    fn drop(&mut self) {
+        let mut jobs = self.cache.lock().unwrap();
+        if jobs.generation != self.generation {
+            return;
+        }
+        if let Some(entry) = jobs.entries.get_mut(&self.key) {
+            entry.computing = false;
+        }
+    }
+}
+
+/// Returns the cached result for a key, and starts computing one on a blocking thread if there
+/// is none, or if a `refresh` is asked for, unless one is already being computed. A cached
+/// result keeps being served while it is refreshed.
+pub fn cached_or_scheduled<K, V>(
+    cache: &Arc<Mutex<JobCache<K, V>>>,
+    key: K,
+    refresh: bool,
+    compute: impl FnOnce() -> V + Send + 'static,
+) -> Option<Arc<Computed<V>>>
+where
+    K: Clone + Eq + Hash + Send + 'static,
+    V: Send + Sync + 'static,
+{
+    let mut jobs = cache.lock().unwrap();
+    let generation = jobs.generation;
+    let entry = jobs.entries.entry(key.clone()).or_insert(JobEntry {
+        result: None,
+        computing: false,
+    });
+    if (entry.result.is_none() || refresh) && !entry.computing {
+        entry.computing = true;
+        let computing = Computing {
+            cache: cache.clone(),
+            key,
+            generation,
+        };
+        tokio::task::spawn_blocking(move || {
+            let value = compute();
+            let mut jobs = computing.cache.lock().unwrap();
+            if jobs.generation == generation {
+                jobs.store(computing.key.clone(), value);
+            }
+        });
+    }
+    entry.result.clone()
+}
+
+#[derive(Serialize)]
+pub struct Pending {
+    status: &'static str,
+}
+
+/// Tells the client its result is being computed and to ask again later.
+pub fn pending() -> Response {
+    (
+        StatusCode::ACCEPTED,
+        Json(Pending {
+            status: "computing",
+        }),
+    )
+        .into_response()
+}
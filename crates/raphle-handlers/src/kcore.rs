@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::Query,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use raphle_experimental::{
+    id_map::NodeKey,
+    kcore::{self, Coreness, DegreeMode},
+    relation::RelationId,
+    snapshot::Snapshot,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::{
+    action::relation_name,
+    jobs::{self, Computed, JobCache},
+    Errors, GraphState,
+};
+
+/// Most core decompositions kept per graph.
+pub(crate) const MAX_CACHED_CORENESS: usize = 16;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct CorenessKey {
+    relation: RelationId,
+    mode: DegreeMode,
+}
+
+/// Core numbers of a graph, by relation and degree mode.
+pub type CorenessCache = JobCache<CorenessKey, Coreness>;
+
+#[derive(Deserialize)]
+pub struct CoreQuery {
+    node: NodeKey,
+    #[serde(default)]
+    mode: DegreeMode,
+    relation: Option<String>,
+    /// Recomputes the core numbers even if they are cached.
+    #[serde(default)]
+    refresh: bool,
+}
+
+#[derive(Serialize)]
+pub struct CoreResponse {
+    computed_at: u64,
+    node: NodeKey,
+    core: Option<u32>,
+    max_core: u32,
+}
+
+/// Requests the core number of a node, counting degrees over edges in either direction
+/// (`total`), or over only `in` or `out` edges. Core numbers are computed in the background on a
+/// snapshot of the graph and cached; until they are ready, `202 Accepted` is returned. Returns
+/// [`Errors::StillLoading`] when the graph is still loading.
+pub async fn get_core(
+    state: Extension<GraphState>,
+    Query(query): Query<CoreQuery>,
+) -> Result<Response, Errors> {
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let Some(relation) = graph.resolve_relation(relation_name(&query.relation)) else {
+        warn!("relation not present");
+        return Ok(Json(CoreResponse {
+            computed_at: 0,
+            node: query.node,
+            core: None,
+            max_core: 0,
+        })
+        .into_response());
+    };
+    let key = CorenessKey {
+        relation,
+        mode: query.mode,
+    };
+    let Some(result) = cached_or_scheduled(&state, key, query.refresh) else {
+        return Ok(jobs::pending());
+    };
+
+    let core = graph
+        .resolve_key(&query.node)
+        .and_then(|nid| result.value.core_of(nid));
+    Ok(Json(CoreResponse {
+        computed_at: result.computed_at,
+        node: query.node,
+        core,
+        max_core: result.value.max_core(),
+    })
+    .into_response())
+}
+
+#[derive(Deserialize)]
+pub struct KCoreQuery {
+    k: u32,
+    #[serde(default)]
+    mode: DegreeMode,
+    relation: Option<String>,
+    /// Largest number of members to return; `size` is always that of the whole k-core.
+    limit: Option<usize>,
+    /// Recomputes the core numbers even if they are cached.
+    #[serde(default)]
+    refresh: bool,
+}
+
+#[derive(Serialize)]
+pub struct KCoreResponse {
+    computed_at: u64,
+    k: u32,
+    size: u64,
+    members: Vec<NodeKey>,
+}
+
+/// Requests the members of the k-core, the nodes with a core number of at least `k`, from the
+/// same core numbers as [`get_core`].
+pub async fn get_kcore(
+    state: Extension<GraphState>,
+    Query(query): Query<KCoreQuery>,
+) -> Result<Response, Errors> {
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let Some(relation) = graph.resolve_relation(relation_name(&query.relation)) else {
+        warn!("relation not present");
+        return Ok(Json(KCoreResponse {
+            computed_at: 0,
+            k: query.k,
+            size: 0,
+            members: vec![],
+        })
+        .into_response());
+    };
+    let key = CorenessKey {
+        relation,
+        mode: query.mode,
+    };
+    let Some(result) = cached_or_scheduled(&state, key, query.refresh) else {
+        return Ok(jobs::pending());
+    };
+
+    let members = result.value.members(query.k);
+    Ok(Json(KCoreResponse {
+        computed_at: result.computed_at,
+        k: query.k,
+        size: members.len(),
+        members: members
+            .iter()
+            .take(query.limit.unwrap_or(usize::MAX))
+            .map(|nid| graph.external_key(nid))
+            .collect(),
+    })
+    .into_response())
+}
+
+/// Returns the cached core numbers for a key, computing them in the background if needed.
+fn cached_or_scheduled(
+    state: &GraphState,
+    key: CorenessKey,
+    refresh: bool,
+) -> Option<Arc<Computed<Coreness>>> {
+    let graph = state.graph.clone();
+    let job_key = key.clone();
+    jobs::cached_or_scheduled(&state.coreness, key, refresh, move || {
+        let snapshot = Snapshot::of(&graph.lock().unwrap(), job_key.relation);
+        let coreness = kcore::coreness(&snapshot, job_key.mode);
+        info!(
+            "found a max core of {} among {} nodes",
+            coreness.max_core(),
+            snapshot.len()
+        );
+        coreness
+    })
+}
@@ -3,18 +3,145 @@ use axum::{
     response::{IntoResponse, Response},
 };
 use raphle_experimental::rwlocked_graph;
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+};
 
 /// Covers all actions one can do to the graph.
 pub mod action;
 
+/// Covers reloading a graph from a new input file, and replaying it to an earlier time.
+pub mod admin;
+
+/// Covers triangle counts and clustering coefficients.
+pub mod clustering;
+
+/// Covers community detection.
+pub mod community;
+
+/// Covers connected components.
+pub mod components;
+
+/// Covers node degrees and the nodes of highest degree.
+pub mod degree;
+
+/// Covers creating, loading, dropping and listing named graphs.
+pub mod graphs;
+
+/// Covers background computations over a whole graph and the caching of their results.
+pub mod jobs;
+
+/// Covers k-core decomposition.
+pub mod kcore;
+
+/// Covers node labels and properties.
+pub mod node;
+
+/// Covers set-algebra queries over neighbor sets, such as mutual follows.
+pub mod query;
+
+/// Covers ranking nodes by importance, computed in the background.
+pub mod rank;
+
+/// Covers who-to-follow recommendations.
+pub mod recommend;
+
 /// Covers the graph health checks.
 pub mod status;
 
+/// Covers multi-hop traversals of the graph.
+pub mod traversal;
+
 /// [`std::sync::Arc`] of an instatiated in-memory graph.
 #[derive(Clone)]
 pub struct GraphState {
     pub graph: Arc<Mutex<rwlocked_graph::RwLockedGraph>>,
+    /// Progress of the running or last reload of the graph.
+    pub reload: Arc<Mutex<Option<admin::ReloadStatus>>>,
+    /// Rankings computed for the graph.
+    pub ranks: Arc<Mutex<rank::RankCache>>,
+    /// Connected components computed for the graph.
+    pub components: Arc<Mutex<components::ComponentCache>>,
+    /// Triangle counts computed for the graph.
+    pub clustering: Arc<Mutex<clustering::ClusteringCache>>,
+    /// Core numbers computed for the graph.
+    pub coreness: Arc<Mutex<kcore::CorenessCache>>,
+    /// Communities detected in the graph.
+    pub communities: Arc<Mutex<community::CommunityCache>>,
+}
+
+impl GraphState {
+    pub fn new(graph: Arc<Mutex<rwlocked_graph::RwLockedGraph>>) -> Self {
+        GraphState {
+            graph,
+            reload: Arc::new(Mutex::new(None)),
+            ranks: Arc::new(Mutex::new(jobs::JobCache::new(rank::MAX_CACHED_RANKINGS))),
+            components: Arc::new(Mutex::new(jobs::JobCache::new(
+                components::MAX_CACHED_COMPONENTS,
+            ))),
+            clustering: Arc::new(Mutex::new(jobs::JobCache::new(
+                clustering::MAX_CACHED_CLUSTERINGS,
+            ))),
+            coreness: Arc::new(Mutex::new(jobs::JobCache::new(kcore::MAX_CACHED_CORENESS))),
+            communities: Arc::new(Mutex::new(jobs::JobCache::new(
+                community::MAX_CACHED_COMMUNITIES,
+            ))),
+        }
+    }
+
+    /// Drops every ranking, partition and count computed for the graph, e.g. once it was
+    /// replaced by a reloaded one.
+    pub fn clear_computed(&self) {
+        self.ranks.lock().unwrap().clear();
+        self.components.lock().unwrap().clear();
+        self.clustering.lock().unwrap().clear();
+        self.coreness.lock().unwrap().clear();
+        self.communities.lock().unwrap().clear();
+    }
+}
+
+/// Named graphs served side by side, each scoped under `/graphs/{name}`.
+#[derive(Clone, Default)]
+pub struct GraphRegistry {
+    graphs: Arc<RwLock<HashMap<String, GraphState>>>,
+}
+
+impl GraphRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a graph under a name, unless the name is taken.
+    pub fn insert(&self, name: &str, state: GraphState) -> Result<(), Errors> {
+        let mut graphs = self.graphs.write().unwrap();
+        if graphs.contains_key(name) {
+            return Err(Errors::GraphExists);
+        }
+        graphs.insert(name.to_string(), state);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<GraphState> {
+        self.graphs.read().unwrap().get(name).cloned()
+    }
+
+    pub fn remove(&self, name: &str) -> Option<GraphState> {
+        self.graphs.write().unwrap().remove(name)
+    }
+
+    /// Returns every registered graph, sorted by name.
+    pub fn all(&self) -> Vec<(String, GraphState)> {
+        let mut graphs: Vec<_> = self
+            .graphs
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, state)| (name.clone(), state.clone()))
+            .collect();
+        graphs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        graphs
+    }
 }
 
 /// Graph-specific errors.
@@ -25,16 +152,116 @@ pub enum Errors {
 
     /// [`Errors::CloggedFlush`] occurs when the graph fails to flush updates to the local database.
     CloggedFlush,
+
+    /// [`Errors::InvalidNode`] occurs when a node key cannot be translated to a node-ID, e.g. a
+    /// string key sent to a graph without ID mapping.
+    InvalidNode,
+
+    /// [`Errors::FailedPrecondition`] occurs when a precondition of a batch does not hold, in
+    /// which case none of its operations are applied.
+    FailedPrecondition,
+
+    /// [`Errors::DirectionMismatch`] occurs when `/outgoing` or `/incoming` is queried on an
+    /// undirected graph, or `/neighbors` on a directed one.
+    DirectionMismatch,
+
+    /// [`Errors::GraphNotFound`] occurs when a route names a graph that is not registered.
+    GraphNotFound,
+
+    /// [`Errors::GraphExists`] occurs when a graph is created under a name that is taken.
+    GraphExists,
+
+    /// [`Errors::GraphFileExists`] occurs when a graph is created under a name whose database
+    /// file is left over from a graph that was dropped without purging it.
+    GraphFileExists,
+
+    /// [`Errors::InvalidGraphName`] occurs when a graph name is empty or contains characters
+    /// other than ASCII letters, digits, `-` and `_`, since it also names the database file, or
+    /// when it would share the database file of the graph loaded on startup.
+    InvalidGraphName,
+
+    /// [`Errors::ReloadInProgress`] occurs when a graph is reloaded while an earlier reload is
+    /// still running.
+    ReloadInProgress,
+
+    /// [`Errors::InvalidQuery`] occurs when query parameters are out of range, e.g. a traversal
+    /// deeper than the server allows.
+    InvalidQuery,
+
+    /// [`Errors::TimedOut`] occurs when a query runs longer than its timeout.
+    TimedOut,
+
+    /// [`Errors::InvalidWeight`] occurs when a weighted path search meets a negative edge weight.
+    InvalidWeight,
+
+    /// [`Errors::ChangesTruncated`] occurs when a graph is replayed to a time whose later changes
+    /// were already dropped from the change log.
+    ChangesTruncated,
+
+    /// [`Errors::InvalidReloadPath`] occurs when a graph is reloaded from a path that does not
+    /// name a file inside the directory reloads are read from.
+    InvalidReloadPath,
+
+    /// [`Errors::DefaultGraph`] occurs when the graph loaded on startup is dropped.
+    DefaultGraph,
+
+    /// [`Errors::PurgeFailed`] occurs when the database file of a dropped graph cannot be
+    /// removed.
+    PurgeFailed,
 }
 
 impl IntoResponse for Errors {
     fn into_response(self) -> Response {
-        let body = match self {
-            Errors::StillLoading => "graph data is still loading to memory",
-            Errors::CloggedFlush => "failed to flush updates to graph",
+        let (status, body) = match self {
+            Errors::StillLoading => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "graph data is still loading to memory",
+            ),
+            Errors::CloggedFlush => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "failed to flush updates to graph",
+            ),
+            Errors::InvalidNode => (
+                StatusCode::BAD_REQUEST,
+                "node key is not valid for this graph",
+            ),
+            Errors::FailedPrecondition => {
+                (StatusCode::CONFLICT, "batch precondition does not hold")
+            }
+            Errors::DirectionMismatch => (
+                StatusCode::BAD_REQUEST,
+                "query does not match whether the graph is directed",
+            ),
+            Errors::GraphNotFound => (StatusCode::NOT_FOUND, "graph does not exist"),
+            Errors::GraphExists => (StatusCode::CONFLICT, "graph already exists"),
+            Errors::GraphFileExists => (
+                StatusCode::CONFLICT,
+                "a database file already exists for this graph",
+            ),
+            Errors::InvalidGraphName => (StatusCode::BAD_REQUEST, "graph name is not valid"),
+            Errors::ReloadInProgress => (StatusCode::CONFLICT, "graph is already reloading"),
+            Errors::InvalidQuery => (StatusCode::BAD_REQUEST, "query parameters are out of range"),
+            Errors::TimedOut => (StatusCode::SERVICE_UNAVAILABLE, "query timed out"),
+            Errors::InvalidWeight => (
+                StatusCode::BAD_REQUEST,
+                "edge weights must be non-negative numbers",
+            ),
+            Errors::ChangesTruncated => (
+                StatusCode::GONE,
+                "changes since that time are no longer kept",
+            ),
+            Errors::InvalidReloadPath => (
+                StatusCode::BAD_REQUEST,
+                "reload path must name a file in the data directory",
+            ),
+            Errors::DefaultGraph => (StatusCode::CONFLICT, "the default graph cannot be dropped"),
+            Errors::PurgeFailed => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to remove the database file of the graph",
+            ),
         };
 
         // just call another implementation of [`IntoResponse`]
-        (StatusCode::SERVICE_UNAVAILABLE, body).into_response()
+        (status, body).into_response()
     }
 }
@@ -0,0 +1,89 @@
+use std::collections::{BTreeMap, HashMap};
+
+use axum::{extract::Query, http::StatusCode, Extension, Json};
+use raphle_experimental::{id_map::NodeKey, node_data::PropertyValue};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{Errors, GraphState};
+
+#[derive(Deserialize)]
+pub struct LabelBody {
+    node: NodeKey,
+    #[serde(default)]
+    add: Vec<String>,
+    #[serde(default)]
+    remove: Vec<String>,
+}
+
+/// Adds and removes labels of a node. Labels are indexed per label, so they can be used as
+/// `label` filters on neighbor queries.
+pub async fn post_labels(
+    state: Extension<GraphState>,
+    Json(body): Json<LabelBody>,
+) -> Result<StatusCode, Errors> {
+    let graph = state.graph.lock().unwrap();
+    let nid = graph.intern_key(&body.node).ok_or(Errors::InvalidNode)?;
+
+    for label in &body.add {
+        graph.add_node_label(nid, label);
+    }
+    for label in &body.remove {
+        graph.remove_node_label(nid, label);
+    }
+
+    info!("updated labels of node {}", body.node);
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+pub struct PropertyBody {
+    node: NodeKey,
+    properties: HashMap<String, Option<PropertyValue>>,
+}
+
+/// Sets properties of a node. A `null` value removes the property.
+pub async fn post_properties(
+    state: Extension<GraphState>,
+    Json(body): Json<PropertyBody>,
+) -> Result<StatusCode, Errors> {
+    let graph = state.graph.lock().unwrap();
+    let nid = graph.intern_key(&body.node).ok_or(Errors::InvalidNode)?;
+
+    for (key, value) in body.properties {
+        graph.set_node_property(nid, &key, value);
+    }
+
+    info!("updated properties of node {}", body.node);
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize)]
+pub struct NodeResponse {
+    labels: Vec<String>,
+    properties: BTreeMap<String, PropertyValue>,
+}
+
+#[derive(Deserialize)]
+pub struct NodeQuery {
+    node: NodeKey,
+}
+
+/// Requests the labels and properties of a node. Unknown nodes have neither.
+pub async fn get_node(
+    state: Extension<GraphState>,
+    Query(query): Query<NodeQuery>,
+) -> Json<NodeResponse> {
+    let graph = state.graph.lock().unwrap();
+    let Some(nid) = graph.resolve_key(&query.node) else {
+        return Json(NodeResponse {
+            labels: vec![],
+            properties: BTreeMap::new(),
+        });
+    };
+
+    Json(NodeResponse {
+        labels: graph.get_node_labels(nid),
+        properties: graph.get_node_properties(nid),
+    })
+}
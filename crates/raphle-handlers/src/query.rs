@@ -0,0 +1,281 @@
+use axum::{extract::Query, Extension, Json};
+use raphle_experimental::{
+    id_map::NodeKey,
+    node_id::NodeSet,
+    rwlocked_graph::{Direction, RwLockedGraph},
+    set_query::{self, Overlap, SetExpr},
+    traversal::TraversalDirection,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use crate::{action::relation_name, Errors, GraphState};
+
+#[derive(Deserialize)]
+pub struct NeighborsOf {
+    node: NodeKey,
+    relation: Option<String>,
+}
+
+/// An expression over node sets, e.g. `{"intersect": [{"out": {"node": 1}}, {"in": {"node": 2}}]}`.
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SetExpression {
+    Out(NeighborsOf),
+    In(NeighborsOf),
+    Both(NeighborsOf),
+    Nodes(Vec<NodeKey>),
+    Union(Vec<SetExpression>),
+    Intersect(Vec<SetExpression>),
+    /// The nodes of the first operand that are not in the second, given as `[first, second]`.
+    Difference(Box<SetExpression>, Box<SetExpression>),
+}
+
+impl SetExpression {
+    /// Resolves the node keys and relations of the expression. Unknown nodes and relations
+    /// stand for empty sets.
+    fn resolve(&self, graph: &RwLockedGraph) -> SetExpr {
+        let neighbors = |of: &NeighborsOf, direction| {
+            let node = graph
+                .resolve_key(&of.node)
+                .and_then(|nid| graph.get_node(nid));
+            let relation = graph.resolve_relation(relation_name(&of.relation));
+            match (node, relation) {
+                (Some(node), Some(relation)) => SetExpr::Neighbors {
+                    node,
+                    relation,
+                    direction,
+                },
+                _ => SetExpr::Nodes(NodeSet::new()),
+            }
+        };
+
+        match self {
+            SetExpression::Out(of) => neighbors(of, TraversalDirection::Out),
+            SetExpression::In(of) => neighbors(of, TraversalDirection::In),
+            SetExpression::Both(of) => neighbors(of, TraversalDirection::Both),
+            SetExpression::Nodes(keys) => SetExpr::Nodes(
+                keys.iter()
+                    .filter_map(|key| graph.resolve_key(key))
+                    .filter_map(|nid| graph.get_node(nid))
+                    .collect(),
+            ),
+            SetExpression::Union(operands) => SetExpr::Union(
+                operands
+                    .iter()
+                    .map(|operand| operand.resolve(graph))
+                    .collect(),
+            ),
+            SetExpression::Intersect(operands) => SetExpr::Intersect(
+                operands
+                    .iter()
+                    .map(|operand| operand.resolve(graph))
+                    .collect(),
+            ),
+            SetExpression::Difference(left, right) => SetExpr::Difference(
+                Box::new(left.resolve(graph)),
+                Box::new(right.resolve(graph)),
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SetOutput {
+    /// The nodes of the result and their count.
+    #[default]
+    Nodes,
+    /// Only the count.
+    Count,
+}
+
+#[derive(Deserialize)]
+pub struct SetQueryBody {
+    expr: SetExpression,
+    #[serde(default)]
+    output: SetOutput,
+    /// Node after which a page of the result starts, in internal ID order.
+    after: Option<NodeKey>,
+    /// Largest number of nodes to return.
+    limit: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct SetQueryResponse {
+    count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nodes: Option<Vec<NodeKey>>,
+    /// Node to pass as `after` for the next page, when there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next: Option<NodeKey>,
+}
+
+/// Evaluates a set expression over neighbor sets under a single read lock, and returns the
+/// resulting nodes or only their count. With `limit`, a page of the nodes is returned along with
+/// the cursor of the next page; `count` is always that of the whole result. Returns
+/// [`Errors::InvalidQuery`] when `after` is an unknown key and [`Errors::StillLoading`] when the
+/// graph is still loading.
+pub async fn post_set_query(
+    state: Extension<GraphState>,
+    Json(body): Json<SetQueryBody>,
+) -> Result<Json<SetQueryResponse>, Errors> {
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let mut result = body.expr.resolve(&graph).evaluate(&graph);
+    let count = result.len();
+    if let SetOutput::Count = body.output {
+        return Ok(Json(SetQueryResponse {
+            count,
+            nodes: None,
+            next: None,
+        }));
+    }
+
+    if let Some(after) = &body.after {
+        let after = graph.resolve_key(after).ok_or(Errors::InvalidQuery)?;
+        result.remove_range(..=after);
+    }
+    let limit = body.limit.unwrap_or(u64::MAX);
+    let page: Vec<_> = result.iter().take(limit as usize).collect();
+    let next = match page.last() {
+        Some(&last) if result.len() > limit => Some(graph.external_key(last)),
+        _ => None,
+    };
+
+    Ok(Json(SetQueryResponse {
+        count,
+        nodes: Some(
+            page.into_iter()
+                .map(|nid| graph.external_key(nid))
+                .collect(),
+        ),
+        next,
+    }))
+}
+
+fn default_sample() -> usize {
+    3
+}
+
+#[derive(Serialize)]
+pub struct OverlapResponse {
+    count: u64,
+    /// The most followed nodes of the result, for display.
+    sample: Vec<NodeKey>,
+    nodes: Vec<NodeKey>,
+}
+
+impl OverlapResponse {
+    fn empty() -> Self {
+        OverlapResponse {
+            count: 0,
+            sample: vec![],
+            nodes: vec![],
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MutualsQuery {
+    a: NodeKey,
+    b: NodeKey,
+    relation: Option<String>,
+    #[serde(default = "default_sample")]
+    sample: usize,
+    /// Largest number of nodes to return; `count` is always that of the whole result.
+    limit: Option<usize>,
+}
+
+/// Requests the nodes both `a` and `b` have an outgoing edge to, e.g. the accounts two users
+/// both follow, with a `sample` of the most followed ones. Returns [`Errors::StillLoading`]
+/// when the graph is still loading.
+pub async fn get_mutuals(
+    state: Extension<GraphState>,
+    Query(query): Query<MutualsQuery>,
+) -> Result<Json<OverlapResponse>, Errors> {
+    overlap_of(
+        &state,
+        (&query.a, Direction::Outgoing),
+        (&query.b, Direction::Outgoing),
+        &query.relation,
+        query.sample,
+        query.limit,
+    )
+}
+
+#[derive(Deserialize)]
+pub struct FollowedByQuery {
+    viewer: NodeKey,
+    node: NodeKey,
+    relation: Option<String>,
+    #[serde(default = "default_sample")]
+    sample: usize,
+    /// Largest number of nodes to return; `count` is always that of the whole result.
+    limit: Option<usize>,
+}
+
+/// Requests the nodes `viewer` has an outgoing edge to that also have one to `node`, e.g. the
+/// accounts a user follows that also follow another account, with a `sample` of the most
+/// followed ones for "followed by X, Y and 12 others". Returns [`Errors::StillLoading`] when
+/// the graph is still loading.
+pub async fn get_followed_by(
+    state: Extension<GraphState>,
+    Query(query): Query<FollowedByQuery>,
+) -> Result<Json<OverlapResponse>, Errors> {
+    overlap_of(
+        &state,
+        (&query.viewer, Direction::Outgoing),
+        (&query.node, Direction::Incoming),
+        &query.relation,
+        query.sample,
+        query.limit,
+    )
+}
+
+fn overlap_of(
+    state: &GraphState,
+    (left, left_direction): (&NodeKey, Direction),
+    (right, right_direction): (&NodeKey, Direction),
+    relation: &Option<String>,
+    sample: usize,
+    limit: Option<usize>,
+) -> Result<Json<OverlapResponse>, Errors> {
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let left = graph.resolve_key(left).and_then(|nid| graph.get_node(nid));
+    let right = graph.resolve_key(right).and_then(|nid| graph.get_node(nid));
+    let relation = graph.resolve_relation(relation_name(relation));
+    let (Some(left), Some(right), Some(relation)) = (left, right, relation) else {
+        warn!("node or relation not present");
+        return Ok(Json(OverlapResponse::empty()));
+    };
+
+    let Overlap { nodes, sample } = set_query::overlap(
+        &graph,
+        (left, left_direction),
+        (right, right_direction),
+        relation,
+        sample,
+    );
+    Ok(Json(OverlapResponse {
+        count: nodes.len(),
+        sample: sample
+            .into_iter()
+            .map(|nid| graph.external_key(nid))
+            .collect(),
+        nodes: nodes
+            .iter()
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|nid| graph.external_key(nid))
+            .collect(),
+    }))
+}
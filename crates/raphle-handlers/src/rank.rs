@@ -0,0 +1,376 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::Query,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use raphle_experimental::{
+    centrality::{self, Centrality},
+    change_log,
+    id_map::NodeKey,
+    node_id::NodeId,
+    rank::{self, PageRank, PageRankParams, Ranking},
+    relation::RelationId,
+    rwlocked_graph::RwLockedGraph,
+    snapshot::Snapshot,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::{
+    action::relation_name,
+    jobs::{self, Computed, JobCache},
+    Errors, GraphState,
+};
+
+/// Most rankings kept per graph.
+pub(crate) const MAX_CACHED_RANKINGS: usize = 64;
+
+/// Most nodes a single request may ask for.
+const MAX_RANK_LIMIT: usize = 1000;
+
+/// Most iterations a single ranking may run.
+const MAX_RANK_ITERATIONS: usize = 1000;
+
+/// Sources sampled for a centrality when no `samples` are given.
+const DEFAULT_CENTRALITY_SAMPLES: usize = 256;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RankAlgorithm {
+    #[default]
+    PageRank,
+    Betweenness,
+    Closeness,
+    Harmonic,
+}
+
+/// Parameters a ranking was computed with. Those another algorithm takes are left at their
+/// defaults, so they do not split the cache.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct RankKey {
+    relation: RelationId,
+    algorithm: RankAlgorithm,
+    /// Sorted seeds of a personalized ranking.
+    seeds: Option<Vec<NodeId>>,
+    damping: u64,
+    tolerance: u64,
+    max_iterations: usize,
+    /// Sources sampled for a centrality.
+    samples: usize,
+    seed: u64,
+}
+
+/// A ranking computed by any of the algorithms.
+pub enum Ranked {
+    PageRank(PageRank),
+    Centrality(Centrality),
+}
+
+impl Ranked {
+    fn ranking(&self) -> &Ranking {
+        match self {
+            Ranked::PageRank(pagerank) => &pagerank.ranking,
+            Ranked::Centrality(centrality) => &centrality.ranking,
+        }
+    }
+
+    fn iterations(&self) -> Option<usize> {
+        match self {
+            Ranked::PageRank(pagerank) => Some(pagerank.iterations),
+            Ranked::Centrality(_) => None,
+        }
+    }
+
+    fn converged(&self) -> Option<bool> {
+        match self {
+            Ranked::PageRank(pagerank) => Some(pagerank.converged),
+            Ranked::Centrality(_) => None,
+        }
+    }
+
+    fn samples(&self) -> Option<usize> {
+        match self {
+            Ranked::PageRank(_) => None,
+            Ranked::Centrality(centrality) => Some(centrality.samples),
+        }
+    }
+}
+
+/// Rankings of a graph, by the parameters they were computed with.
+pub type RankCache = JobCache<RankKey, Ranked>;
+
+fn default_limit() -> usize {
+    10
+}
+
+#[derive(Deserialize)]
+pub struct RankQuery {
+    node: Option<NodeKey>,
+    #[serde(default)]
+    algorithm: RankAlgorithm,
+    relation: Option<String>,
+    /// Comma-separated nodes to personalize a PageRank to.
+    seeds: Option<String>,
+    damping: Option<f64>,
+    tolerance: Option<f64>,
+    max_iterations: Option<usize>,
+    /// Number of source nodes a centrality follows shortest paths from; all nodes when at least
+    /// as many as the graph has.
+    samples: Option<usize>,
+    /// Seed picking the sampled sources of a centrality.
+    #[serde(default)]
+    seed: u64,
+    #[serde(default = "default_limit")]
+    limit: usize,
+    /// Recomputes the ranking even if one is cached.
+    #[serde(default)]
+    refresh: bool,
+}
+
+impl RankQuery {
+    fn params(&self) -> Result<PageRankParams, Errors> {
+        let defaults = PageRankParams::default();
+        let params = PageRankParams {
+            damping: self.damping.unwrap_or(defaults.damping),
+            tolerance: self.tolerance.unwrap_or(defaults.tolerance),
+            max_iterations: self.max_iterations.unwrap_or(defaults.max_iterations),
+        };
+
+        let is_valid = params.damping > 0.0
+            && params.damping < 1.0
+            && params.tolerance > 0.0
+            && params.tolerance.is_finite()
+            && (1..=MAX_RANK_ITERATIONS).contains(&params.max_iterations)
+            && self.samples != Some(0)
+            && self.limit <= MAX_RANK_LIMIT;
+        if !is_valid {
+            return Err(Errors::InvalidQuery);
+        }
+        Ok(params)
+    }
+}
+
+#[derive(Serialize)]
+pub struct RankedNode {
+    node: NodeKey,
+    score: f64,
+}
+
+#[derive(Serialize)]
+pub struct RankTopResponse {
+    computed_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iterations: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    converged: Option<bool>,
+    /// Sources a centrality was sampled from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    samples: Option<usize>,
+    nodes: Vec<RankedNode>,
+}
+
+#[derive(Serialize)]
+pub struct RankNodeResponse {
+    computed_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iterations: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    converged: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    samples: Option<usize>,
+    node: NodeKey,
+    score: Option<f64>,
+    /// Position of the node in the ranking, counting from one.
+    rank: Option<usize>,
+}
+
+/// Requests the `limit` nodes with the highest PageRank, or personalized PageRank given
+/// `seeds`. With `algorithm=betweenness`, `closeness` or `harmonic`, nodes are ranked by that
+/// centrality instead, estimated from shortest paths out of `samples` source nodes picked with
+/// `seed`. Rankings are computed in the background on a snapshot of the graph and cached by
+/// their parameters. Until one is ready, `202 Accepted` is returned; with `refresh`, a new one
+/// is computed while the cached one keeps being served. Returns [`Errors::InvalidQuery`] for
+/// out of range parameters and [`Errors::StillLoading`] when the graph is still loading.
+pub async fn get_rank_top(
+    state: Extension<GraphState>,
+    Query(query): Query<RankQuery>,
+) -> Result<Response, Errors> {
+    let params = query.params()?;
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let Some(relation) = graph.resolve_relation(relation_name(&query.relation)) else {
+        warn!("relation not present");
+        let pagerank = query.algorithm == RankAlgorithm::PageRank;
+        return Ok(Json(RankTopResponse {
+            computed_at: change_log::now(),
+            iterations: pagerank.then_some(0),
+            converged: pagerank.then_some(true),
+            samples: (!pagerank).then_some(0),
+            nodes: vec![],
+        })
+        .into_response());
+    };
+    let key = rank_key(&graph, relation, &query, &params)?;
+    let Some(result) = cached_or_scheduled(&state, key, params, query.refresh) else {
+        return Ok(jobs::pending());
+    };
+
+    let nodes = result
+        .value
+        .ranking()
+        .top(query.limit)
+        .into_iter()
+        .map(|(nid, score)| RankedNode {
+            node: graph.external_key(nid),
+            score,
+        })
+        .collect();
+    Ok(Json(RankTopResponse {
+        computed_at: result.computed_at,
+        iterations: result.value.iterations(),
+        converged: result.value.converged(),
+        samples: result.value.samples(),
+        nodes,
+    })
+    .into_response())
+}
+
+/// Requests the score of a `node` and its position in the ranking, taking the same
+/// parameters as [`get_rank_top`]. Returns [`Errors::InvalidQuery`] without a node.
+pub async fn get_rank(
+    state: Extension<GraphState>,
+    Query(query): Query<RankQuery>,
+) -> Result<Response, Errors> {
+    let params = query.params()?;
+    let Some(node) = query.node.clone() else {
+        return Err(Errors::InvalidQuery);
+    };
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let Some(relation) = graph.resolve_relation(relation_name(&query.relation)) else {
+        warn!("relation not present");
+        let pagerank = query.algorithm == RankAlgorithm::PageRank;
+        return Ok(Json(RankNodeResponse {
+            computed_at: change_log::now(),
+            iterations: pagerank.then_some(0),
+            converged: pagerank.then_some(true),
+            samples: (!pagerank).then_some(0),
+            node,
+            score: None,
+            rank: None,
+        })
+        .into_response());
+    };
+    let key = rank_key(&graph, relation, &query, &params)?;
+    let Some(result) = cached_or_scheduled(&state, key, params, query.refresh) else {
+        return Ok(jobs::pending());
+    };
+
+    let score = graph
+        .resolve_key(&node)
+        .and_then(|nid| result.value.ranking().score(nid));
+    Ok(Json(RankNodeResponse {
+        computed_at: result.computed_at,
+        iterations: result.value.iterations(),
+        converged: result.value.converged(),
+        samples: result.value.samples(),
+        node,
+        score: score.map(|(score, _)| score),
+        rank: score.map(|(_, position)| position + 1),
+    })
+    .into_response())
+}
+
+/// Builds the cache key of a ranking. Returns [`Errors::InvalidQuery`] when `seeds` are given
+/// for PageRank but none of them is a node of the graph.
+fn rank_key(
+    graph: &RwLockedGraph,
+    relation: RelationId,
+    query: &RankQuery,
+    params: &PageRankParams,
+) -> Result<RankKey, Errors> {
+    let seeds = query.seeds.as_ref().map(|seeds| {
+        let mut seeds: Vec<NodeId> = seeds
+            .split(',')
+            .filter_map(|key| graph.resolve_key(&NodeKey::from(key.trim())))
+            .filter(|&nid| graph.get_node(nid).is_some())
+            .collect();
+        seeds.sort_unstable();
+        seeds.dedup();
+        seeds
+    });
+
+    let samples = query.samples.unwrap_or(DEFAULT_CENTRALITY_SAMPLES);
+    let defaults = PageRankParams::default();
+    match query.algorithm {
+        RankAlgorithm::PageRank if seeds.as_ref().is_some_and(Vec::is_empty) => {
+            warn!("none of the seeds are present");
+            Err(Errors::InvalidQuery)
+        }
+        RankAlgorithm::PageRank => Ok(RankKey {
+            relation,
+            algorithm: query.algorithm,
+            seeds,
+            damping: params.damping.to_bits(),
+            tolerance: params.tolerance.to_bits(),
+            max_iterations: params.max_iterations,
+            samples: 0,
+            seed: 0,
+        }),
+        _ => Ok(RankKey {
+            relation,
+            algorithm: query.algorithm,
+            seeds: None,
+            damping: defaults.damping.to_bits(),
+            tolerance: defaults.tolerance.to_bits(),
+            max_iterations: defaults.max_iterations,
+            samples,
+            seed: query.seed,
+        }),
+    }
+}
+
+/// Returns the cached ranking for a key, computing one in the background if needed.
+fn cached_or_scheduled(
+    state: &GraphState,
+    key: RankKey,
+    params: PageRankParams,
+    refresh: bool,
+) -> Option<Arc<Computed<Ranked>>> {
+    let graph = state.graph.clone();
+    let job_key = key.clone();
+    jobs::cached_or_scheduled(&state.ranks, key, refresh, move || {
+        let snapshot = Snapshot::of(&graph.lock().unwrap(), job_key.relation);
+        let samples = Some(job_key.samples);
+        let centrality = match job_key.algorithm {
+            RankAlgorithm::PageRank => {
+                let pagerank = rank::pagerank(&snapshot, &params, job_key.seeds.as_deref());
+                info!(
+                    "ranked {} nodes in {} iterations",
+                    snapshot.len(),
+                    pagerank.iterations
+                );
+                return Ranked::PageRank(pagerank);
+            }
+            RankAlgorithm::Betweenness => centrality::betweenness(&snapshot, samples, job_key.seed),
+            RankAlgorithm::Closeness => centrality::closeness(&snapshot, samples, job_key.seed),
+            RankAlgorithm::Harmonic => centrality::harmonic(&snapshot, samples, job_key.seed),
+        };
+        info!(
+            "ranked {} nodes from {} sampled sources",
+            snapshot.len(),
+            centrality.samples
+        );
+        Ranked::Centrality(centrality)
+    })
+}
@@ -0,0 +1,106 @@
+use axum::{extract::Query, Extension, Json};
+use raphle_experimental::{
+    id_map::NodeKey,
+    recommend::{self, Recommendation, ScorerKind},
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use crate::{action::relation_name, Errors, GraphState};
+
+/// Most recommendations a single request may ask for.
+const MAX_RECOMMEND_LIMIT: usize = 1000;
+
+/// Highest `max_fanout` a request may set, since the scan grows with the square of it.
+const MAX_FANOUT: u64 = 10_000;
+
+fn default_limit() -> usize {
+    10
+}
+
+fn default_max_fanout() -> u64 {
+    1_000
+}
+
+#[derive(Deserialize)]
+pub struct RecommendQuery {
+    node: NodeKey,
+    #[serde(default = "default_limit")]
+    limit: usize,
+    relation: Option<String>,
+    /// Relation whose edges, either way, rule a candidate out, e.g. `blocks`.
+    block_relation: Option<String>,
+    #[serde(default)]
+    scorer: ScorerKind,
+    #[serde(default = "default_max_fanout")]
+    max_fanout: u64,
+}
+
+#[derive(Serialize)]
+pub struct RecommendationResponse {
+    node: NodeKey,
+    score: f64,
+}
+
+#[derive(Serialize)]
+pub struct RecommendResponse {
+    recommendations: Vec<RecommendationResponse>,
+    truncated: bool,
+}
+
+/// Requests the top `limit` friends of friends of a node it has no edge to yet, scored by
+/// `common_neighbors`, `adamic_adar` or `jaccard`. Neighbors beyond `max_fanout`, and neighbors
+/// with more outgoing edges than that, are skipped and reported through `truncated`. Returns
+/// [`Errors::InvalidQuery`] for limits over a thousand or a `max_fanout` over ten thousand, and
+/// [`Errors::StillLoading`] when the graph is still loading.
+pub async fn get_recommendations(
+    state: Extension<GraphState>,
+    Query(query): Query<RecommendQuery>,
+) -> Result<Json<RecommendResponse>, Errors> {
+    if query.limit > MAX_RECOMMEND_LIMIT || query.max_fanout > MAX_FANOUT {
+        return Err(Errors::InvalidQuery);
+    }
+
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let node = graph
+        .resolve_key(&query.node)
+        .and_then(|nid| graph.get_node(nid));
+    let relation = graph.resolve_relation(relation_name(&query.relation));
+    let (Some(node), Some(relation)) = (node, relation) else {
+        warn!("node or relation not present");
+        return Ok(Json(RecommendResponse {
+            recommendations: vec![],
+            truncated: false,
+        }));
+    };
+    let block_relation = query
+        .block_relation
+        .as_deref()
+        .and_then(|name| graph.resolve_relation(name));
+
+    let recommendations = recommend::recommend(
+        &graph,
+        node,
+        relation,
+        block_relation,
+        query.scorer.scorer(),
+        query.limit,
+        query.max_fanout,
+    );
+    Ok(Json(RecommendResponse {
+        recommendations: recommendations
+            .candidates
+            .into_iter()
+            .map(|Recommendation { node, score }| RecommendationResponse {
+                node: graph.external_key(node),
+                score,
+            })
+            .collect(),
+        truncated: recommendations.truncated,
+    }))
+}
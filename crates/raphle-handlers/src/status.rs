@@ -2,7 +2,7 @@ use axum::{extract::Query, response::IntoResponse, Extension, Json};
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
-use super::GraphState;
+use super::{admin::ReloadStatus, GraphState};
 
 /// [`HealthStatusQuery`] is the simplest way to check on an in-memory graph.
 #[derive(Deserialize)]
@@ -18,6 +18,8 @@ pub struct HealthStatus {
     node_count: Option<u32>,
     edge_count: Option<u32>,
     loaded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reload: Option<ReloadStatus>,
 }
 
 /// Calls to the [`GraphState`] for its [`HealthStatus`] via a [`HealthStatusQuery`]
@@ -31,6 +33,7 @@ pub async fn health(
         node_count: None,
         edge_count: None,
         loaded: *state.graph.lock().unwrap().is_loaded.read().unwrap(),
+        reload: state.reload.lock().unwrap().clone(),
     };
 
     // if stats are requested, query them from the graph
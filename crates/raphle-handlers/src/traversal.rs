@@ -0,0 +1,346 @@
+use std::time::{Duration, Instant};
+
+use axum::{extract::Query, Extension, Json};
+use raphle_experimental::{
+    id_map::NodeKey,
+    node_id::{NodeId, NodeSet},
+    rwlocked_graph::RwLockedGraph,
+    traversal::{self, Neighborhood, TraversalDirection},
+    weighted_path::{self, Route, SearchError},
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use crate::{action::relation_name, Errors, GraphState};
+
+/// Deepest neighborhood a single request may ask for.
+const MAX_NEIGHBORHOOD_DEPTH: usize = 6;
+
+/// Most nodes a neighborhood is cut down to when no `limit` is given.
+const DEFAULT_NEIGHBORHOOD_LIMIT: u64 = 1_000;
+
+/// Most nodes a single neighborhood request may ask for.
+const MAX_NEIGHBORHOOD_LIMIT: u64 = 100_000;
+
+fn default_neighborhood_limit() -> u64 {
+    DEFAULT_NEIGHBORHOOD_LIMIT
+}
+
+#[derive(Serialize)]
+pub struct NeighborhoodResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nodes: Option<Vec<NodeKey>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    layers: Option<Vec<Vec<NodeKey>>>,
+    truncated: bool,
+}
+
+#[derive(Deserialize)]
+pub struct NeighborhoodQuery {
+    node: NodeKey,
+    depth: usize,
+    #[serde(default)]
+    direction: TraversalDirection,
+    relation: Option<String>,
+    #[serde(default = "default_neighborhood_limit")]
+    limit: u64,
+    layers: Option<bool>,
+}
+
+/// Requests every node within `depth` hops of a node, following edges `out`, `in` or `both`
+/// ways. Returns the nodes as one set, or grouped by hop when `layers` is set, and at most
+/// `limit` of them, a thousand by default. Returns [`Errors::InvalidQuery`] for depths over six
+/// or limits over [`MAX_NEIGHBORHOOD_LIMIT`], and [`Errors::StillLoading`] when the graph is
+/// still loading.
+pub async fn get_neighborhood(
+    state: Extension<GraphState>,
+    Query(query): Query<NeighborhoodQuery>,
+) -> Result<Json<NeighborhoodResponse>, Errors> {
+    if query.depth > MAX_NEIGHBORHOOD_DEPTH || query.limit > MAX_NEIGHBORHOOD_LIMIT {
+        return Err(Errors::InvalidQuery);
+    }
+
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let node = graph
+        .resolve_key(&query.node)
+        .and_then(|nid| graph.get_node(nid));
+    let relation = graph.resolve_relation(relation_name(&query.relation));
+    let neighborhood = match (node, relation) {
+        (Some(node), Some(relation)) => traversal::neighborhood(
+            &graph,
+            node,
+            relation,
+            query.direction,
+            query.depth,
+            Some(query.limit),
+        ),
+        _ => {
+            warn!("node or relation not present");
+            Neighborhood {
+                layers: vec![],
+                truncated: false,
+            }
+        }
+    };
+
+    let (nodes, layers) = if query.layers.unwrap_or(false) {
+        let layers = neighborhood
+            .layers
+            .iter()
+            .map(|layer| external_keys(&graph, layer))
+            .collect();
+        (None, Some(layers))
+    } else {
+        (Some(external_keys(&graph, &neighborhood.nodes())), None)
+    };
+
+    Ok(Json(NeighborhoodResponse {
+        nodes,
+        layers,
+        truncated: neighborhood.truncated,
+    }))
+}
+
+/// Translates a set of internal IDs to their external keys.
+fn external_keys(graph: &RwLockedGraph, nodes: &NodeSet) -> Vec<NodeKey> {
+    nodes.iter().map(|nid| graph.external_key(nid)).collect()
+}
+
+/// Longest path a single request may search for.
+const MAX_PATH_DEPTH: usize = 12;
+
+/// Most shortest paths a single request may ask for.
+const MAX_PATH_LIMIT: usize = 1_000;
+
+fn default_max_depth() -> usize {
+    6
+}
+
+#[derive(Serialize)]
+pub struct PathResponse {
+    reachable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    distance: Option<usize>,
+    paths: Vec<Vec<NodeKey>>,
+}
+
+#[derive(Deserialize)]
+pub struct PathQuery {
+    source: NodeKey,
+    target: NodeKey,
+    relation: Option<String>,
+    #[serde(default = "default_max_depth")]
+    max_depth: usize,
+    limit: Option<usize>,
+}
+
+/// Requests a shortest path along outgoing edges from a source to a target node, or up to
+/// `limit` of them, together with their distance. Answers `reachable: false` when the target
+/// cannot be reached within `max_depth` hops. Returns [`Errors::InvalidQuery`] for depths over
+/// twelve or limits over a thousand, and [`Errors::StillLoading`] when the graph is still
+/// loading.
+pub async fn get_path(
+    state: Extension<GraphState>,
+    Query(query): Query<PathQuery>,
+) -> Result<Json<PathResponse>, Errors> {
+    let limit = query.limit.unwrap_or(1);
+    if query.max_depth > MAX_PATH_DEPTH || limit == 0 || limit > MAX_PATH_LIMIT {
+        return Err(Errors::InvalidQuery);
+    }
+
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let source = graph
+        .resolve_key(&query.source)
+        .and_then(|nid| graph.get_node(nid));
+    let target = graph
+        .resolve_key(&query.target)
+        .and_then(|nid| graph.get_node(nid));
+    let relation = graph.resolve_relation(relation_name(&query.relation));
+    let shortest_paths = match (source, target, relation) {
+        (Some(source), Some(target), Some(relation)) => {
+            traversal::shortest_paths(&graph, source, target, relation, query.max_depth, limit)
+        }
+        _ => {
+            warn!("source, target or relation not present");
+            None
+        }
+    };
+
+    let Some(shortest_paths) = shortest_paths else {
+        return Ok(Json(PathResponse {
+            reachable: false,
+            distance: None,
+            paths: vec![],
+        }));
+    };
+    let paths = shortest_paths
+        .paths
+        .iter()
+        .map(|path| path.iter().map(|&nid| graph.external_key(nid)).collect())
+        .collect();
+
+    Ok(Json(PathResponse {
+        reachable: true,
+        distance: Some(shortest_paths.distance),
+        paths,
+    }))
+}
+
+/// Longest a weighted path search may run, in milliseconds. The search holds the graph lock on
+/// a request thread, so it is kept short.
+const MAX_SEARCH_TIMEOUT_MS: u64 = 250;
+
+fn default_timeout_ms() -> u64 {
+    100
+}
+
+/// Estimate of the remaining cost to the target that turns a weighted path search into A*.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Heuristic {
+    /// Straight-line distance between the `x` and `y` properties of two nodes. Edge weights
+    /// must be at least the distance between their nodes for paths to be the cheapest.
+    Euclidean,
+}
+
+#[derive(Serialize)]
+pub struct RouteResponse {
+    target: NodeKey,
+    cost: f64,
+    path: Vec<NodeKey>,
+}
+
+#[derive(Serialize)]
+pub struct WeightedPathResponse {
+    routes: Vec<RouteResponse>,
+}
+
+#[derive(Deserialize)]
+pub struct WeightedPathQuery {
+    source: NodeKey,
+    target: Option<NodeKey>,
+    budget: Option<f64>,
+    relation: Option<String>,
+    /// Edge property holding the weights, `weight` by default.
+    weight: Option<String>,
+    heuristic: Option<Heuristic>,
+    #[serde(default = "default_timeout_ms")]
+    timeout_ms: u64,
+}
+
+/// Requests the cheapest path along outgoing edges from a source to a `target`, or to every
+/// node within a cost `budget`, cheapest first. Edges cost their `weight` property, or one
+/// without it. Returns [`Errors::InvalidQuery`] unless exactly one of `target` and `budget` is
+/// given, [`Errors::InvalidWeight`] on negative weights, [`Errors::TimedOut`] when the search
+/// runs longer than `timeout_ms`, and [`Errors::StillLoading`] when the graph is still loading.
+pub async fn get_weighted_path(
+    state: Extension<GraphState>,
+    Query(query): Query<WeightedPathQuery>,
+) -> Result<Json<WeightedPathResponse>, Errors> {
+    let is_valid_budget = query
+        .budget
+        .map_or(true, |budget| budget.is_finite() && budget >= 0.0);
+    if query.target.is_some() == query.budget.is_some()
+        || !is_valid_budget
+        || query.timeout_ms > MAX_SEARCH_TIMEOUT_MS
+    {
+        return Err(Errors::InvalidQuery);
+    }
+    let deadline = Instant::now() + Duration::from_millis(query.timeout_ms);
+
+    let graph = state.graph.lock().unwrap();
+    if !*graph.is_loaded.read().unwrap() {
+        error!("graph data not yet loaded!");
+        return Err(Errors::StillLoading);
+    }
+
+    let source = graph
+        .resolve_key(&query.source)
+        .and_then(|nid| graph.get_node(nid));
+    let relation = graph.resolve_relation(relation_name(&query.relation));
+    let (Some(source), Some(relation)) = (source, relation) else {
+        warn!("source or relation not present");
+        return Ok(Json(WeightedPathResponse { routes: vec![] }));
+    };
+    let weight_property = query.weight.as_deref().unwrap_or("weight");
+
+    let routes = match (&query.target, query.budget) {
+        (Some(target), _) => {
+            let Some(target) = graph
+                .resolve_key(target)
+                .and_then(|nid| graph.get_node(nid))
+            else {
+                warn!("target not present");
+                return Ok(Json(WeightedPathResponse { routes: vec![] }));
+            };
+
+            let target_coordinates = coordinates(&graph, target);
+            let euclidean = |nid| match (coordinates(&graph, nid), target_coordinates) {
+                (Some((x, y)), Some((target_x, target_y))) => (x - target_x).hypot(y - target_y),
+                _ => 0.0,
+            };
+            let heuristic: Option<&dyn Fn(NodeId) -> f64> = match query.heuristic {
+                Some(Heuristic::Euclidean) => Some(&euclidean),
+                None => None,
+            };
+
+            weighted_path::cheapest_path(
+                &graph,
+                source,
+                target,
+                relation,
+                weight_property,
+                heuristic,
+                deadline,
+            )
+            .map(|route| route.into_iter().collect())
+        }
+        (None, Some(budget)) => weighted_path::cheapest_paths_within(
+            &graph,
+            source,
+            relation,
+            weight_property,
+            budget,
+            deadline,
+        ),
+        (None, None) => unreachable!("either a target or a budget is given"),
+    };
+
+    let routes: Vec<Route> = routes.map_err(|e| match e {
+        SearchError::TimedOut => Errors::TimedOut,
+        SearchError::InvalidWeight => Errors::InvalidWeight,
+    })?;
+    let routes = routes
+        .into_iter()
+        .map(|route| RouteResponse {
+            target: graph.external_key(route.target),
+            cost: route.cost,
+            path: route
+                .path
+                .iter()
+                .map(|&nid| graph.external_key(nid))
+                .collect(),
+        })
+        .collect();
+
+    Ok(Json(WeightedPathResponse { routes }))
+}
+
+/// Returns the `x` and `y` properties of a node, if both are numbers.
+fn coordinates(graph: &RwLockedGraph, nid: NodeId) -> Option<(f64, f64)> {
+    let properties = graph.get_node_properties(nid);
+    Some((
+        properties.get("x")?.as_f64()?,
+        properties.get("y")?.as_f64()?,
+    ))
+}
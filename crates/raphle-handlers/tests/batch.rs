@@ -0,0 +1,79 @@
+use std::sync::{Arc, Mutex};
+
+use axum::{http::StatusCode, response::IntoResponse, Extension, Json};
+use raphle_experimental::id_map::NodeKey;
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+use raphle_handlers::action::{post_batch, BatchBody};
+use raphle_handlers::GraphState;
+
+fn mapped_graph() -> GraphState {
+    let graph = RwLockedGraph::with_id_mapping(16);
+    *graph.is_loaded.write().unwrap() = true;
+    GraphState::new(Arc::new(Mutex::new(graph)))
+}
+
+fn body(json: &str) -> Json<BatchBody> {
+    Json::from_bytes(json.as_bytes()).unwrap()
+}
+
+#[tokio::test]
+async fn registers_nothing_for_a_rejected_batch() {
+    let state = mapped_graph();
+    let batch = body(
+        r#"{
+            "preconditions": [{"type": "out_degree_below", "node": "alice", "limit": 0}],
+            "ops": [{"op": "add_edge", "source": "alice", "target": "bob", "relation": "likes"}]
+        }"#,
+    );
+
+    let status = match post_batch(Extension(state.clone()), batch).await {
+        Ok(status) => status,
+        Err(e) => e.into_response().status(),
+    };
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let graph = state.graph.lock().unwrap();
+    assert_eq!(graph.resolve_key(&NodeKey::from("alice")), None);
+    assert_eq!(graph.resolve_key(&NodeKey::from("bob")), None);
+    assert_eq!(graph.resolve_relation("likes"), None);
+}
+
+#[tokio::test]
+async fn interns_keys_of_an_applied_batch() {
+    let state = mapped_graph();
+    let batch = body(
+        r#"{
+            "preconditions": [{"type": "edge_absent", "source": "alice", "target": "bob"}],
+            "ops": [{"op": "add_edge", "source": "alice", "target": "bob", "relation": "likes"}]
+        }"#,
+    );
+
+    let status = post_batch(Extension(state.clone()), batch).await.ok();
+    assert_eq!(status, Some(StatusCode::OK));
+
+    let graph = state.graph.lock().unwrap();
+    let alice = graph.resolve_key(&NodeKey::from("alice")).unwrap();
+    let bob = graph.resolve_key(&NodeKey::from("bob")).unwrap();
+    let likes = graph.resolve_relation("likes").unwrap();
+    assert!(graph.has_edge(alice, bob, likes));
+}
+
+#[tokio::test]
+async fn rejects_keys_the_graph_cannot_map() {
+    let graph = RwLockedGraph::new(16);
+    *graph.is_loaded.write().unwrap() = true;
+    let state = GraphState::new(Arc::new(Mutex::new(graph)));
+    let batch = body(
+        r#"{"ops": [
+            {"op": "add_edge", "source": 1, "target": 2, "relation": "likes"},
+            {"op": "add_edge", "source": "alice", "target": 2}
+        ]}"#,
+    );
+
+    let status = match post_batch(Extension(state.clone()), batch).await {
+        Ok(status) => status,
+        Err(e) => e.into_response().status(),
+    };
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(state.graph.lock().unwrap().resolve_relation("likes"), None);
+}
@@ -0,0 +1,136 @@
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    body::Body,
+    extract::{Path, Query},
+    http::{Request, StatusCode, Uri},
+    middleware,
+    response::IntoResponse,
+    routing::get,
+    Extension, Json, Router,
+};
+use raphle_experimental::rwlocked_graph::{RwLockedGraph, DEFAULT_DB_PATH};
+use raphle_handlers::graphs::{
+    db_path, delete_graph, post_graph, scope_graph, CreateGraphBody, DeleteGraphQuery,
+    DEFAULT_GRAPH,
+};
+use raphle_handlers::{Errors, GraphRegistry, GraphState};
+use tower::ServiceExt;
+
+fn state() -> GraphState {
+    GraphState::new(Arc::new(Mutex::new(RwLockedGraph::new(16))))
+}
+
+fn status(result: Result<StatusCode, Errors>) -> StatusCode {
+    match result {
+        Ok(status) => status,
+        Err(e) => e.into_response().status(),
+    }
+}
+
+async fn create(registry: &GraphRegistry, name: &str) -> StatusCode {
+    let body = format!(r#"{{"name": "{}"}}"#, name);
+    let body = Json::<CreateGraphBody>::from_bytes(body.as_bytes()).unwrap();
+    status(post_graph(Extension(registry.clone()), body).await)
+}
+
+async fn drop_graph(registry: &GraphRegistry, name: &str, query: &str) -> StatusCode {
+    let uri: Uri = format!("/?{}", query).parse().unwrap();
+    let query = Query::<DeleteGraphQuery>::try_from_uri(&uri).unwrap();
+    status(delete_graph(Extension(registry.clone()), Path(name.to_string()), query).await)
+}
+
+#[test]
+fn rejects_duplicate_names() {
+    let registry = GraphRegistry::new();
+    assert!(registry.insert("follows", state()).is_ok());
+
+    let taken = registry.insert("follows", state()).err().unwrap();
+    assert_eq!(taken.into_response().status(), StatusCode::CONFLICT);
+    assert_eq!(registry.all().len(), 1);
+}
+
+#[tokio::test]
+async fn rejects_names_that_are_not_valid_file_names() {
+    let registry = GraphRegistry::new();
+    let default_name = DEFAULT_DB_PATH
+        .strip_prefix("data/")
+        .and_then(|file| file.strip_suffix(".db"))
+        .unwrap();
+    assert_eq!(db_path(default_name), DEFAULT_DB_PATH);
+
+    for name in ["", "../x", "a b", default_name] {
+        assert_eq!(create(&registry, name).await, StatusCode::BAD_REQUEST);
+    }
+    assert!(registry.all().is_empty());
+}
+
+#[tokio::test]
+async fn refuses_to_drop_the_default_graph() {
+    let registry = GraphRegistry::new();
+    assert!(registry.insert(DEFAULT_GRAPH, state()).is_ok());
+
+    assert_eq!(
+        drop_graph(&registry, DEFAULT_GRAPH, "purge=true").await,
+        StatusCode::CONFLICT
+    );
+    assert!(registry.get(DEFAULT_GRAPH).is_some());
+}
+
+#[tokio::test]
+async fn scopes_requests_to_registered_graphs() {
+    let registry = GraphRegistry::new();
+    assert!(registry.insert("follows", state()).is_ok());
+    let named_graph_routes = Router::new()
+        .route("/status", get(|| async { StatusCode::OK }))
+        .route_layer(middleware::from_fn(scope_graph));
+    let app = Router::new()
+        .nest("/graphs/:name", named_graph_routes)
+        .layer(Extension(registry));
+
+    let request = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+    let response = app
+        .clone()
+        .oneshot(request("/graphs/follows/status"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app.oneshot(request("/graphs/likes/status")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn keeps_the_database_file_of_dropped_graphs_unless_purged() {
+    let name = format!("graphs-test-{}", std::process::id());
+    let path = db_path(&name);
+    let registry = GraphRegistry::new();
+
+    assert_eq!(create(&registry, &name).await, StatusCode::CREATED);
+    assert_eq!(create(&registry, &name).await, StatusCode::CONFLICT);
+    fs::create_dir_all("data").unwrap();
+    fs::write(&path, "").unwrap();
+    fs::write(format!("{}-wal", path), "").unwrap();
+
+    // the file left by a plain drop keeps the name from being reused
+    assert_eq!(drop_graph(&registry, &name, "").await, StatusCode::OK);
+    assert_eq!(create(&registry, &name).await, StatusCode::CONFLICT);
+    assert!(registry.get(&name).is_none());
+    assert_eq!(
+        drop_graph(&registry, &name, "").await,
+        StatusCode::NOT_FOUND
+    );
+
+    // a purge removes the file along with its WAL files
+    assert!(registry.insert(&name, state()).is_ok());
+    assert_eq!(
+        drop_graph(&registry, &name, "purge=true").await,
+        StatusCode::OK
+    );
+    assert!(!std::path::Path::new(&path).exists());
+    assert!(!std::path::Path::new(&format!("{}-wal", path)).exists());
+    assert_eq!(create(&registry, &name).await, StatusCode::CREATED);
+
+    // only succeeds when no other test left files behind
+    let _ = fs::remove_dir("data");
+}
@@ -0,0 +1,51 @@
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+use raphle_handlers::jobs::{cached_or_scheduled, Computed, JobCache};
+
+type Cache = Arc<Mutex<JobCache<u32, u32>>>;
+
+/// Asks for a key until its result is computed.
+async fn wait_for(cache: &Cache, key: u32, value: u32) -> Arc<Computed<u32>> {
+    for _ in 0..200 {
+        if let Some(result) = cached_or_scheduled(cache, key, false, move || value) {
+            return result;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("job for key {} never finished", key);
+}
+
+#[tokio::test]
+async fn caches_computed_results() {
+    let cache: Cache = Arc::new(Mutex::new(JobCache::new(4)));
+
+    assert!(cached_or_scheduled(&cache, 1, false, || 10).is_none());
+    assert_eq!(wait_for(&cache, 1, 10).await.value, 10);
+    // a cached result is served without computing it again
+    assert_eq!(wait_for(&cache, 1, 20).await.value, 10);
+}
+
+#[tokio::test]
+async fn reschedules_jobs_that_panicked() {
+    let cache: Cache = Arc::new(Mutex::new(JobCache::new(4)));
+
+    assert!(cached_or_scheduled(&cache, 1, false, || panic!("job failed")).is_none());
+    assert_eq!(wait_for(&cache, 1, 10).await.value, 10);
+}
+
+#[tokio::test]
+async fn drops_results_of_jobs_started_before_a_clear() {
+    let cache: Cache = Arc::new(Mutex::new(JobCache::new(4)));
+    let (release, released) = mpsc::channel::<()>();
+
+    cached_or_scheduled(&cache, 1, false, move || {
+        released.recv().unwrap();
+        10
+    });
+    cache.lock().unwrap().clear();
+    release.send(()).unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(wait_for(&cache, 1, 20).await.value, 20);
+}
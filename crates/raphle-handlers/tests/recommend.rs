@@ -0,0 +1,36 @@
+use std::sync::{Arc, Mutex};
+
+use axum::{extract::Query, http::StatusCode, http::Uri, response::IntoResponse, Extension};
+use raphle_experimental::relation::DEFAULT_RELATION;
+use raphle_experimental::rwlocked_graph::RwLockedGraph;
+use raphle_handlers::recommend::{get_recommendations, RecommendQuery};
+use raphle_handlers::GraphState;
+
+/// Serves a graph where 1 and 2 both follow 3.
+fn state() -> GraphState {
+    let graph = RwLockedGraph::new(16);
+    *graph.is_loaded.write().unwrap() = true;
+    let rel = graph.intern_relation(DEFAULT_RELATION);
+    graph.add_edge(1, 3, rel);
+    graph.add_edge(2, 3, rel);
+    GraphState::new(Arc::new(Mutex::new(graph)))
+}
+
+async fn status(query: &str) -> StatusCode {
+    let uri: Uri = format!("/recommend?{}", query).parse().unwrap();
+    let query = Query::<RecommendQuery>::try_from_uri(&uri).unwrap();
+    match get_recommendations(Extension(state()), query).await {
+        Ok(_) => StatusCode::OK,
+        Err(e) => e.into_response().status(),
+    }
+}
+
+#[tokio::test]
+async fn caps_the_limit_and_the_fan_out() {
+    assert_eq!(status("node=1&max_fanout=10000").await, StatusCode::OK);
+    assert_eq!(
+        status("node=1&max_fanout=10001").await,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(status("node=1&limit=1001").await, StatusCode::BAD_REQUEST);
+}
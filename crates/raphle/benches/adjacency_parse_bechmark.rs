@@ -1,6 +1,6 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use dotenvy::dotenv;
-use raphle_experimental::rwlocked_graph;
+use raphle_experimental::{relation::DEFAULT_RELATION, rwlocked_graph};
 use std::{
     sync::{Arc, Mutex},
     time::Duration,
@@ -28,11 +28,12 @@ fn adjacency_parse_benchmark(c: &mut Criterion) {
     c.bench_function("load_graph", |b| {
         b.iter(|| {
             tokio::runtime::Runtime::new().unwrap().block_on(async {
-                match graph_clone
-                    .lock()
-                    .unwrap()
-                    .load_from_csv(&csv_path_clone, Some(b' '))
-                {
+                match graph_clone.lock().unwrap().load_from_csv(
+                    &csv_path_clone,
+                    Some(b' '),
+                    DEFAULT_RELATION,
+                    &[],
+                ) {
                     Ok(_) => info!("Loaded graph from CSV"),
                     Err(e) => warn!("Failed to load graph from CSV: {}", e),
                 }
@@ -1,5 +1,6 @@
 use axum::{
-    routing::{get, post},
+    middleware,
+    routing::{delete, get, post},
     Extension, Router,
 };
 use axum_prometheus::{
@@ -8,13 +9,93 @@ use axum_prometheus::{
 };
 use dotenvy::dotenv;
 use metrics_process::Collector;
-use std::sync::{Arc, Mutex};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use tracing::{info, warn};
 
 // use raphle_graph::graph;
 
-use raphle_experimental::rwlocked_graph;
-use raphle_handlers::GraphState;
+use raphle_experimental::{change_log, relation::DEFAULT_RELATION, rwlocked_graph};
+use raphle_handlers::{admin::ReloadDir, GraphRegistry, GraphState};
+
+/// Routes served for every graph, both at the root for the graph loaded on startup and under
+/// `/graphs/{name}` for each named graph.
+fn graph_routes() -> Router {
+    Router::new()
+        .route("/health", get(raphle_handlers::status::health))
+        .route("/has_edge", get(raphle_handlers::action::get_has_edge))
+        .route("/edge", post(raphle_handlers::action::post_edge))
+        .route("/edges", post(raphle_handlers::action::post_edges))
+        .route("/batch", post(raphle_handlers::action::post_batch))
+        .route(
+            "/outgoing",
+            get(raphle_handlers::action::get_outgoing_edges),
+        )
+        .route(
+            "/incoming",
+            get(raphle_handlers::action::get_incoming_edges),
+        )
+        .route("/neighbors", get(raphle_handlers::action::get_neighbors))
+        .route("/degree", get(raphle_handlers::degree::get_degree))
+        .route("/degree/top", get(raphle_handlers::degree::get_degree_top))
+        .route(
+            "/degree/histogram",
+            get(raphle_handlers::degree::get_degree_histogram),
+        )
+        .route(
+            "/neighborhood",
+            get(raphle_handlers::traversal::get_neighborhood),
+        )
+        .route("/path", get(raphle_handlers::traversal::get_path))
+        .route(
+            "/weighted_path",
+            get(raphle_handlers::traversal::get_weighted_path),
+        )
+        .route("/query/sets", post(raphle_handlers::query::post_set_query))
+        .route("/mutuals", get(raphle_handlers::query::get_mutuals))
+        .route("/followed_by", get(raphle_handlers::query::get_followed_by))
+        .route(
+            "/clustering",
+            get(raphle_handlers::clustering::get_node_clustering),
+        )
+        .route(
+            "/clustering/global",
+            get(raphle_handlers::clustering::get_clustering),
+        )
+        .route("/community", get(raphle_handlers::community::get_community))
+        .route(
+            "/communities",
+            get(raphle_handlers::community::get_communities),
+        )
+        .route(
+            "/component",
+            get(raphle_handlers::components::get_component),
+        )
+        .route(
+            "/components",
+            get(raphle_handlers::components::get_components),
+        )
+        .route("/core", get(raphle_handlers::kcore::get_core))
+        .route("/kcore", get(raphle_handlers::kcore::get_kcore))
+        .route("/rank", get(raphle_handlers::rank::get_rank))
+        .route("/rank/top", get(raphle_handlers::rank::get_rank_top))
+        .route(
+            "/recommend",
+            get(raphle_handlers::recommend::get_recommendations),
+        )
+        .route("/changes", get(raphle_handlers::action::get_changes))
+        .route("/node", get(raphle_handlers::node::get_node))
+        .route("/labels", post(raphle_handlers::node::post_labels))
+        .route("/properties", post(raphle_handlers::node::post_properties))
+        .route(
+            "/flush_updates",
+            get(raphle_handlers::action::get_flush_updates),
+        )
+        .route("/admin/reload", post(raphle_handlers::admin::post_reload))
+        .route("/admin/replay", post(raphle_handlers::admin::post_replay))
+}
 
 #[tokio::main]
 async fn main() {
@@ -41,6 +122,10 @@ async fn main() {
         .unwrap_or("1000000".to_string())
         .parse::<u32>()
         .unwrap();
+    // map external node keys (u64 IDs, DIDs, handles) to dense internal IDs
+    let id_mapping = std::env::var("NODE_ID_MAPPING").unwrap_or("false".to_string()) == "true";
+    // undirected graphs store every edge in both directions, e.g. friendships
+    let directed = std::env::var("GRAPH_DIRECTED").unwrap_or("true".to_string()) != "false";
 
     info!(
         "raphle started on port {} with node capacity of {}",
@@ -48,20 +133,44 @@ async fn main() {
     );
     info!("Starting up!");
 
-    let graph = rwlocked_graph::RwLockedGraph::new(expected_node_count);
+    let graph = if id_mapping {
+        info!("node ID mapping enabled");
+        rwlocked_graph::RwLockedGraph::with_id_mapping(expected_node_count)
+    } else {
+        rwlocked_graph::RwLockedGraph::new(expected_node_count)
+    };
+    let graph = if directed {
+        graph
+    } else {
+        info!("undirected graph mode enabled");
+        graph.undirected()
+    };
+    // how long flushed changes are kept, which bounds how far back the graph can be replayed
+    let graph = match std::env::var("CHANGE_LOG_RETENTION_SECS") {
+        Ok(retention) => graph.with_change_retention(retention.parse::<u64>().unwrap()),
+        Err(_) => graph,
+    };
     let graph = Arc::new(Mutex::new(graph));
 
     let graph_clone = graph.clone();
     let csv_path_clone = csv_path.clone();
 
     let delim: Option<u8> = Some(b' ');
+    let relation = std::env::var("BENCHMARK_RELATION").unwrap_or(DEFAULT_RELATION.to_string());
+    // names of the edge property columns following source and target, e.g. "created_at,weight"
+    let property_columns = std::env::var("BENCHMARK_PROPERTY_COLUMNS").unwrap_or_default();
 
     tokio::spawn(async move {
-        match graph_clone
-            .lock()
-            .unwrap()
-            .load_from_csv(&csv_path_clone, delim)
-        {
+        let property_columns: Vec<&str> = property_columns
+            .split(',')
+            .filter(|column| !column.is_empty())
+            .collect();
+        match graph_clone.lock().unwrap().load_from_csv(
+            &csv_path_clone,
+            delim,
+            &relation,
+            &property_columns,
+        ) {
             Ok(_) => info!("Loaded graph from CSV"),
             Err(e) => warn!("Failed to load graph from CSV: {}", e),
         }
@@ -69,11 +178,49 @@ async fn main() {
     .await
     .expect("Failed to spawn task");
 
-    let state = GraphState { graph };
+    // reap expired edges in the background
+    let reaper_interval = std::env::var("REAPER_INTERVAL_SECS")
+        .unwrap_or("60".to_string())
+        .parse::<u64>()
+        .unwrap();
+    let state = GraphState::new(graph);
+
+    // graphs are only reloaded from files in this directory, by default that of the startup file
+    let reload_dir = std::env::var("RELOAD_DATA_DIR").unwrap_or_else(|_| {
+        let csv_dir = std::path::Path::new(&csv_path).parent();
+        csv_dir.map_or(project_path.clone(), |dir| {
+            dir.to_string_lossy().to_string()
+        })
+    });
+
+    // the graph loaded on startup is also served as a named graph
+    let registry = GraphRegistry::new();
+    if registry
+        .insert(raphle_handlers::graphs::DEFAULT_GRAPH, state.clone())
+        .is_err()
+    {
+        unreachable!("registry starts out empty");
+    }
+
+    let reaper_registry = registry.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(reaper_interval));
+        loop {
+            interval.tick().await;
+            for (name, state) in reaper_registry.all() {
+                let reaped = state.graph.lock().unwrap().reap_expired(change_log::now());
+                if reaped > 0 {
+                    info!("reaped {} expired edges from graph {}", reaped, name);
+                }
+            }
+        }
+    });
 
     let collector = Collector::default();
     collector.describe();
 
+    // requests are labelled by their route, named graphs count their requests under a `graph`
+    // label in `raphle_graph_requests_total`
     let metric_layer = PrometheusMetricLayer::new();
     // this is the default if you use [`PrometheusMetricLayer:pair`]
     let metric_handle = PrometheusBuilder::new()
@@ -88,24 +235,19 @@ async fn main() {
         .install_recorder()
         .unwrap();
 
-    let server = Router::new()
-        .route("/health", get(raphle_handlers::status::health))
-        .route("/has_edge", get(raphle_handlers::action::get_has_edge))
-        .route("/edge", post(raphle_handlers::action::post_edge))
-        .route("/edges", post(raphle_handlers::action::post_edges))
-        .route(
-            "/outgoing",
-            get(raphle_handlers::action::get_outgoing_edges),
-        )
-        .route(
-            "/incoming",
-            get(raphle_handlers::action::get_incoming_edges),
-        )
+    let named_graph_routes = graph_routes()
+        .route("/", delete(raphle_handlers::graphs::delete_graph))
+        .route_layer(middleware::from_fn(raphle_handlers::graphs::scope_graph));
+
+    let server = graph_routes()
+        .layer(Extension(state))
         .route(
-            "/flush_updates",
-            get(raphle_handlers::action::get_flush_updates),
+            "/graphs",
+            get(raphle_handlers::graphs::list_graphs).post(raphle_handlers::graphs::post_graph),
         )
-        .layer(Extension(state))
+        .nest("/graphs/:name", named_graph_routes)
+        .layer(Extension(registry))
+        .layer(Extension(ReloadDir::new(reload_dir)))
         .route(
             "/metrics",
             get(|| async move {